@@ -1,240 +1,3382 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri_plugin_window_state::WindowExt;
+#[cfg(target_os = "windows")]
 use winreg::enums::*;
+#[cfg(target_os = "windows")]
 use winreg::RegKey;
 use std::fs;
-use serde::Serialize;
-use std::collections::HashMap;
-use tauri::Manager;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use flate2::read::GzDecoder;
+use zip::result::ZipError;
+use zip::ZipArchive;
+use sysinfo::{ProcessesToUpdate, System};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use tauri_plugin_window_state::AppHandleExt;
+use tauri_plugin_opener::OpenerExt;
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct StatsResult {
     scenario_name: String,
+    category: Option<String>,  // Clicking, Tracking or Switching for known VT S5 scenarios
     score: f64,
+    score_scale: Option<String>,  // the footer's Score Mode, when present
     kills: i32,
     hits: i32,
     misses: i32,
+    accuracy: Option<f64>,  // percent, None when no shots were logged
+    efficiency: Option<f64>,  // score times accuracy as a fraction, None without accuracy
+    shots: Option<i32>,
+    damage_done: Option<f64>,
+    damage_possible: Option<f64>,
+    avg_ttk: Option<f64>,  // seconds
+    reaction_time: Option<f64>,  // milliseconds
     fov_scale: String,
     fov: f64,
+    fov_horizontal_deg: Option<f64>,  // fov normalized to horizontal degrees at this resolution
     resolution: String,
-    avg_fps: f64,
+    resolution_width: Option<i32>,
+    resolution_height: Option<i32>,
+    avg_fps: Option<f64>,  // rounded to 0.1, None when KovaaK logged 0 or nothing
+    sens_scale: String,  // e.g. "cm/360", "Valorant", "CS2"
+    horiz_sens: f64,  // in sens_scale units
+    vert_sens: f64,
+    sens_ratio: Option<f64>,  // horiz / vert, None when vert sens is 0 or missing
     sens_cm: Option<(f64, f64)>,  // (horiz, vert) if using cm/360
-    date: String
+    sens_cm_computed: Option<(f64, f64)>,  // (horiz, vert) converted from sens_scale, see sens_scale_yaw
+    dpi: Option<f64>,  // None when the footer doesn't record it
+    date: String,
+    timestamp: Option<i64>,  // Unix seconds, None if the date couldn't be parsed
+    kills_detail: Vec<KillEntry>,
+    per_bot_accuracy: HashMap<String, f64>,  // mean kill-table accuracy per Bot, empty without a Bot column
+    shot_timeline: Option<Vec<ShotEvent>>,  // None unless the file has a per-shot table
+    game_version: Option<String>,
+    crosshair: Option<String>,  // reticle image from the footer, e.g. "Default.png"
+    input_lag: Option<f64>,
+    max_fps_config: Option<f64>,
+    source_path: String  // canonical path of the CSV this run was parsed from
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct KillEntry {
+    ttk: f64,  // seconds
+    shots: i32,
+    hits: i32,
+    accuracy: f64,
+    bot: Option<String>  // None when the table has no Bot column
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ShotEvent {
+    offset: f64,  // seconds since the first logged shot
+    hit: bool
+}
+
+#[derive(Debug, Serialize)]
+struct ScenarioInfo {
+    name: String,
+    play_count: u32
+}
+
+#[derive(Debug, Serialize)]
+struct SkippedFile {
+    filename: String,
+    reason: String
+}
+
+// Directory walk telemetry reported in PathResult
+#[derive(Debug, Default, Clone, Copy)]
+struct ScanCounts {
+    scanned_files: usize,  // directory entries looked at
+    matched_files: usize,  // entries that were parsed
+    scan_ms: u64  // walk and parse time
+}
+
+struct CachedRun {
+    modified: SystemTime,
+    footer_only: bool,  // parsed by parse_footer_only, without the tables
+    stat: StatsResult
+}
+
+// Parsed runs keyed by path, reused until the file's mtime changes. A full
+// parse also serves footer-only requests, not the other way around.
+#[derive(Default)]
+struct StatsCache(Mutex<HashMap<PathBuf, CachedRun>>);
+
+impl StatsCache {
+    fn parse(&self, path: &Path, footer_only: bool) -> Result<StatsResult, String> {
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+
+        if let Some(modified) = modified {
+            if let Some(cached) = self.0.lock().unwrap().get(path) {
+                if cached.modified == modified && (footer_only || !cached.footer_only) {
+                    return Ok(cached.stat.clone());
+                }
+            }
+        }
+
+        let result = if footer_only { parse_footer_only(path) } else { parse_csv_file(path) };
+        if let (Ok(stat), Some(modified)) = (&result, modified) {
+            self.0.lock().unwrap().insert(
+                path.to_path_buf(),
+                CachedRun { modified, footer_only, stat: stat.clone() },
+            );
+        }
+        result
+    }
+}
+
+#[derive(Default)]
+struct StatsWatcher(Mutex<Option<RecommendedWatcher>>);
+
+// Rank thresholds used by the rank and energy commands. Starts as the embedded
+// table and is replaced by load_benchmark_table.
+struct ThresholdTable(Mutex<BenchmarkTable>);
+
+impl Default for ThresholdTable {
+    fn default() -> Self {
+        ThresholdTable(Mutex::new(
+            parse_benchmark_table(EMBEDDED_THRESHOLDS).expect("embedded threshold table should be valid"),
+        ))
+    }
+}
+
+// One entry of a threshold file for load_benchmark_table
+#[derive(Debug, Deserialize)]
+struct RankThreshold {
+    rank: String,
+    threshold: f64
+}
+
+#[derive(Debug, Serialize)]
+struct ScenarioSummary {
+    run_count: usize,
+    best_score: f64,
+    average_score: f64,
+    median_score: f64,
+    std_dev: f64,  // population standard deviation
+    best_date: String
+}
+
+#[derive(Debug, Serialize)]
+struct SmoothedHistory {
+    runs: Vec<StatsResult>,
+    moving_average: Vec<f64>,  // parallel to runs
+    window: usize  // window actually used after clamping
+}
+
+#[derive(Debug, Serialize)]
+struct ScenarioEnergy {
+    scenario_name: String,
+    best_score: Option<f64>,  // None when the scenario hasn't been played
+    energy: f64,
+    weight: f64,
+    weighted_energy: f64
+}
+
+#[derive(Debug, Serialize)]
+struct OverallBenchmarkScore {
+    overall_energy: f64,
+    contributions: Vec<ScenarioEnergy>,  // sorted by scenario name
+    missing_thresholds: Vec<String>  // scenarios the threshold table doesn't rank
+}
+
+#[derive(Debug, Serialize)]
+struct PersonalBest {
+    scenario_name: String,
+    score: f64,
+    timestamp: i64
+}
+
+#[derive(Debug, Serialize)]
+struct BenchmarkProgress {
+    played: usize,
+    total: usize,
+    missing: Vec<String>  // in the order they were requested
+}
+
+#[derive(Debug, Serialize)]
+struct Session {
+    start: i64,  // Unix seconds of the first run
+    end: i64,  // Unix seconds of the last run
+    run_count: usize,
+    best_score: f64
+}
+
+// Submission constraints for validate_run. Unset fields aren't checked, the
+// 1:1 sens ratio always is.
+#[derive(Debug, Deserialize)]
+struct BenchmarkRules {
+    min_fps: Option<f64>,
+    allowed_fov_scales: Option<Vec<String>>,
+    min_width: Option<i32>,
+    min_height: Option<i32>
+}
+
+#[derive(Debug, Serialize)]
+struct RuleViolation {
+    code: String,  // machine readable, e.g. "avg_fps_below_min"
+    message: String
+}
+
+#[derive(Debug, Serialize)]
+struct ScenarioComparison {
+    scenario_name: String,
+    best_a: Option<f64>,
+    best_b: Option<f64>,
+    delta: Option<f64>  // best_b - best_a, None unless both folders have runs
+}
+
+// Saved by snapshot_benchmark, bests are None for scenarios not played yet
+#[derive(Debug, Serialize, Deserialize)]
+struct BenchmarkSnapshot {
+    created: i64,  // Unix seconds
+    bests: HashMap<String, Option<f64>>
+}
+
+#[derive(Debug, Serialize)]
+struct ScenarioDelta {
+    scenario_name: String,
+    previous: f64,
+    current: f64,
+    delta: f64  // current - previous
+}
+
+#[derive(Debug, Serialize)]
+struct SnapshotDiff {
+    created: i64,  // when the snapshot was taken
+    deltas: Vec<ScenarioDelta>,  // sorted by scenario name
+    newly_played: Vec<String>  // unplayed in the snapshot, played now
+}
+
+#[derive(Debug, Serialize)]
+struct NumberDiff {
+    a: Option<f64>,
+    b: Option<f64>,
+    delta: Option<f64>  // b - a, None unless both runs have the value
+}
+
+#[derive(Debug, Serialize)]
+struct TextDiff {
+    a: String,
+    b: String,
+    changed: bool
+}
+
+#[derive(Debug, Serialize)]
+struct RunDiff {
+    score: NumberDiff,
+    accuracy: NumberDiff,
+    avg_fps: NumberDiff,
+    fov: NumberDiff,
+    fov_scale: TextDiff,
+    horiz_sens: NumberDiff,
+    vert_sens: NumberDiff,
+    sens_scale: TextDiff
+}
+
+#[derive(Debug, Serialize)]
+struct OutdatedRun {
+    scenario_name: String,
+    game_version: Option<String>,
+    reason: String  // "older than <min_version>" or "unknown version"
+}
+
+#[derive(Debug, Serialize)]
+struct RankInfo {
+    rank: String,  // "Unranked" below the first threshold
+    next_rank: Option<String>,
+    points_to_next: Option<f64>
+}
+
+// Scenario -> (rank, threshold) pairs sorted by threshold ascending
+type BenchmarkTable = HashMap<String, Vec<(String, f64)>>;
+
+// Difficulty -> category -> subcategory -> scenario -> rank -> threshold, the
+// same layout as the benchmark gist the frontend loads.
+type RawBenchmarkTable =
+    HashMap<String, HashMap<String, HashMap<String, HashMap<String, HashMap<String, f64>>>>>;
+
+// Fallback used until the frontend hands over the benchmark gist with
+// set_benchmark_table on startup
+const EMBEDDED_THRESHOLDS: &str = include_str!("../resources/vt_s5_thresholds.json");
+
+// Same values as ENERGY_BASE and ENERGY_INCREMENT in the frontend models.ts
+const ENERGY_BASE: f64 = 100.0;
+const ENERGY_INCREMENT: f64 = 100.0;
+const VT_DIFFICULTIES: [&str; 3] = ["Novice", "Intermediate", "Advanced"];
+const RANKS_PER_DIFFICULTY: usize = 4;
+const FUZZY_MAX_DISTANCE: usize = 2;
+// Allowed distance of sens_ratio from 1.0 before validate_run flags it
+const SENS_RATIO_EPSILON: f64 = 0.001;
+// Real stats files are a few KB, anything this big isn't one
+const MAX_STATS_FILE_SIZE: u64 = 10 * 1024 * 1024;
+// How long a watched stats file has to stay unchanged before it's parsed
+const WATCH_SETTLE: Duration = Duration::from_millis(1000);
+const WATCH_POLL: Duration = Duration::from_millis(250);
+
+// Settings that change how highscores are picked, see get_stats
+#[derive(Debug, Default)]
+struct HighscoreOptions {
+    aliases: HashMap<String, String>,
+    tiebreak: Tiebreak,
+    from: Option<i64>,  // inclusive Unix seconds
+    to: Option<i64>,
+    match_mode: MatchMode,
+    min_fps: Option<f64>,  // inclusive
+    sort_by: SortKey,
+    descending: bool,
+    include_ties: bool,
+    lower_is_better: HashSet<String>  // grouping keys whose lowest score is the best
+}
+
+impl HighscoreOptions {
+    // Whether a run may compete for the highscore. Runs without a parsed
+    // timestamp are excluded whenever a date bound is set, and runs without a
+    // logged avg_fps whenever min_fps is set.
+    fn accepts(&self, stat: &StatsResult) -> bool {
+        if let Some(min_fps) = self.min_fps {
+            if !stat.avg_fps.is_some_and(|avg_fps| avg_fps >= min_fps) {
+                return false;
+            }
+        }
+
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        let Some(timestamp) = stat.timestamp else {
+            return false;
+        };
+        self.from.is_none_or(|from| timestamp >= from) && self.to.is_none_or(|to| timestamp <= to)
+    }
+}
+
+// How to choose between runs with the same score
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+enum Tiebreak {
+    #[default]
+    LatestDate,
+    Accuracy
+}
+
+// Order of the stats in a get_stats result. Ties fall back to the name.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+enum SortKey {
+    #[default]
+    Name,
+    Score,
+    Date,
+    Accuracy  // runs without accuracy sort before any value
+}
+
+// How requested scenario names are matched against the names in stats files
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+enum MatchMode {
+    #[default]
+    Exact,  // filename prefix, as before match modes existed
+    CaseInsensitive,  // equal after normalize_scenario_name
+    Fuzzy  // normalized names within FUZZY_MAX_DISTANCE edits
+}
+
+// Errors get_stats reports to the frontend, serialized as {kind, message, ...}
+// so the UI can tell a missing setup apart from a transient failure.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StatsError {
+    SteamNotFound { message: String },
+    StatsFolderNotFound { message: String, searched_paths: Vec<String> },
+    Io { message: String },
+    InvalidDate { message: String }
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::SteamNotFound { message }
+            | StatsError::StatsFolderNotFound { message, .. }
+            | StatsError::Io { message }
+            | StatsError::InvalidDate { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+// The other commands still return plain strings
+impl From<StatsError> for String {
+    fn from(error: StatsError) -> String {
+        error.to_string()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ArchiveResult {
+    moved: Vec<String>,  // source paths of the files that were moved
+    error: Option<String>  // set when a move failed, the files after it were left in place
 }
 
 #[derive(Debug, Serialize)]
 struct PathResult {
     stats_path: String,
     exists: bool,
-    stats: Vec<StatsResult>
+    stats: Vec<StatsResult>,
+    skipped: Vec<SkippedFile>,
+    searched_paths: Vec<String>,  // folders discovery looked at, empty when it didn't run
+    tied_runs: Vec<StatsResult>,  // other runs matching a best score, only with include_ties
+    error: Option<String>,  // set when the folder exists but couldn't be read
+    scanned_files: usize,  // 0 when no folder was scanned
+    matched_files: usize,
+    scan_ms: u64
+}
+
+// Where find_stats_path looked and what it picked
+struct StatsPathSearch {
+    found: Option<PathBuf>,  // first of `existing`
+    existing: Vec<PathBuf>,  // every searched folder that exists, in search order
+    searched: Vec<PathBuf>
+}
+
+// Column positions from the "Kill #" header of the per-kill table. None when a
+// required column is missing, in which case the table is ignored.
+struct KillColumns {
+    ttk: usize,
+    shots: usize,
+    hits: usize,
+    accuracy: usize,
+    bot: Option<usize>
+}
+
+fn kill_columns(header: &str) -> Option<KillColumns> {
+    let header: Vec<&str> = header.split(',').map(|s| s.trim()).collect();
+    let column = |name: &str| header.iter().position(|h| *h == name);
+    Some(KillColumns {
+        ttk: column("TTK")?,
+        shots: column("Shots")?,
+        hits: column("Hits")?,
+        accuracy: column("Accuracy")?,
+        bot: column("Bot")
+    })
+}
+
+// One row of the per-kill table, None if a required field doesn't parse
+fn parse_kill_row(line: &str, columns: &KillColumns) -> Option<KillEntry> {
+    let fields: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+    Some(KillEntry {
+        ttk: fields.get(columns.ttk)?.trim_end_matches('s').parse().ok()?,
+        shots: fields.get(columns.shots)?.parse().ok()?,
+        hits: fields.get(columns.hits)?.parse().ok()?,
+        accuracy: fields.get(columns.accuracy)?.parse().ok()?,
+        bot: columns
+            .bot
+            .and_then(|col| fields.get(col))
+            .filter(|bot| !bot.is_empty())
+            .map(|bot| bot.to_string()),
+    })
+}
+
+// Mean accuracy of the kill rows for each bot, in the kill table's own units
+fn per_bot_accuracy(kills: &[KillEntry]) -> HashMap<String, f64> {
+    let mut totals: HashMap<&str, (f64, usize)> = HashMap::new();
+    for kill in kills {
+        if let Some(bot) = &kill.bot {
+            let total = totals.entry(bot).or_insert((0.0, 0));
+            total.0 += kill.accuracy;
+            total.1 += 1;
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(bot, (sum, count))| (bot.to_string(), sum / count as f64))
+        .collect()
+}
+
+// Parses a footer number, also in the forms non-English locales write them:
+// "1 234,56" or "1.234,56". Spaces are thousands separators, and when both
+// '.' and ',' appear the later one is the decimal separator.
+fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<f64>() {
+        return number.is_finite().then_some(number);
+    }
+
+    let digits: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\u{202f}')
+        .collect();
+    let normalized = match (digits.rfind(','), digits.rfind('.')) {
+        (Some(comma), Some(dot)) if comma > dot => digits.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => digits.replace(',', ""),
+        (Some(_), None) => digits.replace(',', "."),
+        _ => digits,
+    };
+
+    normalized.parse().ok().filter(|number: &f64| number.is_finite())
+}
+
+// Whole-number footer field. An empty value counts as 0, anything else that
+// isn't a whole number fails the file with the raw value in the reason.
+fn parse_count(key: &str, value: &str) -> Result<i32, String> {
+    if value.trim().is_empty() {
+        return Ok(0);
+    }
+
+    parse_number(value)
+        .filter(|number| number.fract() == 0.0 && *number >= i32::MIN as f64 && *number <= i32::MAX as f64)
+        .map(|number| number as i32)
+        .ok_or_else(|| format!("unparseable {} \"{}\"", key, value))
+}
+
+// Decimal footer field. An empty value is None, anything else that isn't a
+// number fails the file with the raw value in the reason, like parse_count.
+fn parse_optional_number(key: &str, value: &str) -> Result<Option<f64>, String> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+
+    parse_number(value)
+        .map(Some)
+        .ok_or_else(|| format!("unparseable {} \"{}\"", key, value))
+}
+
+// Seconds from a clock time like 12:34:56.789 or a plain number of seconds
+fn parse_clock_seconds(value: &str) -> Option<f64> {
+    value.split(':').try_fold(0.0, |total, part| Some(total * 60.0 + part.trim().parse::<f64>().ok()?))
+}
+
+// Column positions from the "Shot #" header of the per-shot table some KovaaK
+// versions write. The per-kill table only has totals per kill, so files
+// without this table have no shot_timeline.
+struct ShotColumns {
+    timestamp: usize,
+    hit: usize
+}
+
+fn shot_columns(header: &str) -> Option<ShotColumns> {
+    let header: Vec<&str> = header.split(',').map(|s| s.trim()).collect();
+    let column = |name: &str| header.iter().position(|h| *h == name);
+    Some(ShotColumns { timestamp: column("Timestamp")?, hit: column("Hit")? })
+}
+
+// (timestamp in seconds, hit) for one row of the per-shot table
+fn parse_shot_row(line: &str, columns: &ShotColumns) -> Option<(f64, bool)> {
+    let fields: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+    let timestamp = parse_clock_seconds(fields.get(columns.timestamp)?)?;
+    let hit = match fields.get(columns.hit)?.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => true,
+        "false" | "0" | "no" => false,
+        _ => return None,
+    };
+    Some((timestamp, hit))
+}
+
+// Converts a KovaaK filename date (YYYY.MM.DD-HH.MM.SS) to Unix seconds. KovaaK
+// writes local wall-clock time without an offset, so it's read in the system
+// time zone.
+fn parse_kovaak_date(date: &str) -> Option<i64> {
+    NaiveDateTime::parse_from_str(date, "%Y.%m.%d-%H.%M.%S")
+        .ok()
+        .and_then(local_timestamp)
+}
+
+// Unix seconds of a local wall-clock time. An ambiguous time (clocks turned
+// back) takes the earlier instant, a skipped one (clocks turned forward) is
+// read an hour later.
+fn local_timestamp(datetime: NaiveDateTime) -> Option<i64> {
+    Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(datetime + chrono::Duration::hours(1))).earliest())
+        .map(|datetime| datetime.timestamp())
+}
+
+// Canonical absolute path as a string. canonicalize adds a \\?\ prefix on
+// Windows that the opener and most tools don't expect, so it's dropped.
+fn canonical_path_string(path: &Path) -> String {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let canonical = canonical.to_string_lossy();
+    match canonical.strip_prefix(r"\\?\") {
+        Some(stripped) if !stripped.starts_with(r"UNC\") => stripped.to_string(),
+        _ => canonical.into_owned(),
+    }
+}
+
+// Numeric parts of a dotted KovaaK version ("3.4.2" or with a build suffix like
+// "3.4.2.2023-07-13-..."). Each part keeps its leading digits and parsing stops
+// at the first part without any. None when there's no number at all.
+fn parse_game_version(version: &str) -> Option<Vec<u64>> {
+    let parts: Vec<u64> = version
+        .trim()
+        .split('.')
+        .map(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .take_while(|part| part.is_some())
+        .flatten()
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+// Compares versions part by part, missing parts count as 0 (3.4 == 3.4.0)
+fn compare_game_versions(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+// Parses an ISO date (2024-05-01) or date-time (2024-05-01T18:30:00) in local
+// time into Unix seconds, like parse_kovaak_date. A bare date covers the whole
+// day, so as an upper bound it resolves to the last second of that day.
+fn parse_iso_bound(value: &str, end_of_day: bool) -> Result<i64, String> {
+    let invalid = || format!("Invalid date \"{}\", expected YYYY-MM-DD", value);
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        return local_timestamp(datetime).ok_or_else(invalid);
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| invalid())?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_opt(23, 59, 59)
+    } else {
+        NaiveTime::from_hms_opt(0, 0, 0)
+    };
+    local_timestamp(date.and_time(time.unwrap_or_default())).ok_or_else(invalid)
+}
+
+// Like str::strip_suffix, ignoring ASCII case
+fn strip_suffix_ignore_case<'a>(value: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = value.len().checked_sub(suffix.len())?;
+    if value.is_char_boundary(split) && value[split..].eq_ignore_ascii_case(suffix) {
+        Some(&value[..split])
+    } else {
+        None
+    }
+}
+
+// Drops the .bak or .gz suffix so the name ends in " Stats.csv" again
+fn strip_archive_suffix(filename: &str) -> &str {
+    strip_suffix_ignore_case(filename, ".bak")
+        .or_else(|| strip_suffix_ignore_case(filename, ".gz"))
+        .unwrap_or(filename)
+}
+
+// Splits "<scenario> - <mode> - <date> Stats.csv" into scenario and date. Both
+// trailing segments are taken from the end of the name, so scenario names that
+// contain " - " themselves (VT - Pasu Goated) stay whole. The extension may be
+// in any case.
+fn split_stats_filename(filename: &str) -> Result<(&str, &str), &'static str> {
+    let stem = strip_suffix_ignore_case(filename, " Stats.csv").ok_or("missing date segment")?;
+    let (rest, date) = stem.rsplit_once(" - ").ok_or("missing date segment")?;
+    if date.trim().is_empty() {
+        return Err("missing date segment");
+    }
+
+    let scenario_name = rest.rsplit_once(" - ").map_or(rest, |(scenario_name, _mode)| scenario_name);
+    if scenario_name.is_empty() {
+        return Err("invalid filename");
+    }
+
+    Ok((scenario_name, date))
+}
+
+// Lines of a stats file streamed through a BufReader, with gzip decoded and a
+// leading UTF-8 BOM removed, which would otherwise stick to the first key.
+// Files over MAX_STATS_FILE_SIZE, before or after decompression, are refused
+// so a stray log can't stall the scan.
+fn stats_file_lines(path: &Path) -> Result<impl Iterator<Item = Result<String, String>>, &'static str> {
+    let size = fs::metadata(path).map_err(|_| "unreadable")?.len();
+    if size > MAX_STATS_FILE_SIZE {
+        return Err("file too large");
+    }
+
+    let file = fs::File::open(path).map_err(|_| "unreadable")?;
+    let (reader, read_error): (Box<dyn Read>, &'static str) =
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gz")) {
+            (Box::new(GzDecoder::new(file)), "corrupt gzip")
+        } else {
+            (Box::new(file), "unreadable")
+        };
+    let mut reader = BufReader::new(reader.take(MAX_STATS_FILE_SIZE + 1));
+    let mut total = 0;
+    let mut first = true;
+
+    Ok(std::iter::from_fn(move || {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => return None,
+            Ok(read) => total += read as u64,
+            Err(_) => return Some(Err(read_error.to_string())),
+        }
+        if total > MAX_STATS_FILE_SIZE {
+            return Some(Err("file too large".to_string()));
+        }
+
+        // Same line endings as str::lines
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        if std::mem::take(&mut first) {
+            if let Some(stripped) = line.strip_prefix('\u{feff}') {
+                line = stripped.to_string();
+            }
+        }
+        Some(Ok(line))
+    }))
+}
+
+// Returns a short reason on failure, which is reported back as a SkippedFile.
+fn parse_csv_file(path: &Path) -> Result<StatsResult, String> {
+    parse_stats_file(path, false)
+}
+
+// Like parse_csv_file but only reads the summary key/value block, leaving
+// kills_detail, per_bot_accuracy and shot_timeline empty. For commands that
+// only need scores and settings.
+fn parse_footer_only(path: &Path) -> Result<StatsResult, String> {
+    parse_stats_file(path, true)
+}
+
+fn parse_stats_file(path: &Path, footer_only: bool) -> Result<StatsResult, String> {
+    let filename = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or("invalid filename")?;
+    let lines = stats_file_lines(path)?;
+    let mut stat = parse_stats_lines(lines, filename, footer_only)?;
+    stat.source_path = canonical_path_string(path);
+    Ok(stat)
+}
+
+// Parses one run from already decoded contents and its file name, so files on
+// disk, zip entries and string fixtures all go through the same parser. The
+// error is the skip reason reported in SkippedFile. source_path is set to
+// `filename`, callers that know the real location replace it. A leading BOM
+// is dropped like in stats_file_lines.
+fn parse_csv_content(content: &str, filename: &str) -> Result<StatsResult, String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    parse_stats_lines(content.lines().map(|line| Ok(line.to_string())), filename, false)
+}
+
+// Where parse_stats_lines is in the file. Each table is read until the first
+// blank line, and only the first table of each kind is used.
+enum Section {
+    Footer,
+    Kills(Option<KillColumns>),
+    Shots(Option<ShotColumns>)
 }
 
-fn parse_csv_file(path: &PathBuf) -> Option<StatsResult> {
-    let content = fs::read_to_string(path).ok()?;
-    let lines = content.lines();
+// Single pass over the lines of a stats file. Every line is also checked for a
+// footer key, table rows never match one since their first colon comes after
+// a comma. With `footer_only` the tables are skipped and left empty.
+fn parse_stats_lines(
+    lines: impl Iterator<Item = Result<String, String>>,
+    filename: &str,
+    footer_only: bool
+) -> Result<StatsResult, String> {
+    let source_path = filename.to_string();
+    let filename = strip_archive_suffix(filename);
+    let (scenario_name, date) = split_stats_filename(filename)?;
+    let scenario_name = scenario_name.to_string();
+    let date = date.to_string();
+    let timestamp = parse_kovaak_date(&date);
+
+    // CRLF endings are handled by the line readers and the trims below.
+    let mut section = Section::Footer;
+    let (mut kills_seen, mut shots_seen) = (false, false);
+    let mut kills_detail = Vec::new();
+    let mut shot_rows: Option<Vec<(f64, bool)>> = None;
+
+    let mut score = None;
+    let mut kills = 0;
+    let mut hits = 0;
+    let mut misses = 0;
+    let mut fov_scale = String::new();
+    let mut fov = 0.0;
+    let mut resolution = String::new();
+    let mut avg_fps = None;
+    let mut sens_scale = String::new();
+    let mut horiz_sens = 0.0;
+    let mut vert_sens = 0.0;
+    let mut game_version = None;
+    let mut crosshair = None;
+    let mut input_lag = None;
+    let mut max_fps_config = None;
+    let mut dpi = None;
+    let mut shots = None;
+    let mut damage_done = None;
+    let mut damage_possible = None;
+    let mut avg_ttk = None;
+    let mut reaction_time = None;
+    let mut score_scale = None;
+
+    // A key that appears more than once (some versions log Avg FPS per phase)
+    // keeps its last value. Every arm below assigns unconditionally, so the
+    // result only depends on which occurrence comes last in the file.
+    for line in lines {
+        let line = line?;
+        let line = line.as_str();
+
+        if footer_only {
+            // Tables are skipped
+        } else if !kills_seen && line.starts_with("Kill #") {
+            kills_seen = true;
+            section = Section::Kills(kill_columns(line));
+        } else if !shots_seen && line.starts_with("Shot #") {
+            shots_seen = true;
+            let columns = shot_columns(line);
+            shot_rows = columns.as_ref().map(|_| Vec::new());
+            section = Section::Shots(columns);
+        } else {
+            match &section {
+                Section::Kills(_) | Section::Shots(_) if line.trim().is_empty() => section = Section::Footer,
+                Section::Kills(Some(columns)) => kills_detail.extend(parse_kill_row(line, columns)),
+                Section::Shots(Some(columns)) => {
+                    if let (Some(rows), Some(row)) = (shot_rows.as_mut(), parse_shot_row(line, columns)) {
+                        rows.push(row);
+                    }
+                }
+                Section::Kills(None) | Section::Shots(None) | Section::Footer => {}
+            }
+        }
+
+        // Split on the first colon only, values like Challenge Start contain more
+        let Some((key, value)) = line.split_once(':') else { continue; };
+
+        let key = key.trim();
+        let value = value.trim();
+
+        match key {
+            "Score" => {
+                let raw = value.trim_start_matches(',');
+                score = Some(parse_number(raw).ok_or_else(|| format!("unparseable Score \"{}\"", raw))?);
+            }
+            "Kills" => kills = parse_count(key, value.trim_start_matches(','))?,
+            "Hit Count" => hits = parse_count(key, value.trim_start_matches(','))?,
+            "Miss Count" => misses = parse_count(key, value.trim_start_matches(','))?,
+            "FOVScale" => fov_scale = value.trim_start_matches(',').to_string(),
+            "FOV" => fov = parse_optional_number(key, value.trim_start_matches(','))?.unwrap_or(0.0),
+            "Resolution" => resolution = value.trim_start_matches(',').to_string(),
+            "Avg FPS" => {
+                avg_fps = parse_optional_number(key, value.trim_start_matches(','))?
+                    .filter(|fps| *fps > 0.0)
+                    .map(|fps| (fps * 10.0).round() / 10.0);
+            }
+            "Sens Scale" => sens_scale = value.trim_start_matches(',').to_string(),
+            "Horiz Sens" => horiz_sens = parse_optional_number(key, value.trim_start_matches(','))?.unwrap_or(0.0),
+            "Vert Sens" => vert_sens = parse_optional_number(key, value.trim_start_matches(','))?.unwrap_or(0.0),
+            "Score Mode" => {
+                let mode = value.trim_start_matches(',');
+                score_scale = (!mode.is_empty()).then(|| mode.to_string());
+            }
+            "Game Version" => {
+                let version = value.trim_start_matches(',');
+                game_version = (!version.is_empty()).then(|| version.to_string());
+            }
+            "Crosshair" => {
+                let reticle = value.trim_start_matches(',');
+                crosshair = (!reticle.is_empty()).then(|| reticle.to_string());
+            }
+            "Input Lag" => input_lag = parse_optional_number(key, value.trim_start_matches(','))?,
+            "Max FPS (config)" => max_fps_config = parse_optional_number(key, value.trim_start_matches(','))?,
+            "Mouse DPI" | "DPI" => dpi = parse_optional_number(key, value.trim_start_matches(','))?,
+            "Shots" => {
+                let raw = value.trim_start_matches(',');
+                shots = if raw.is_empty() { None } else { Some(parse_count(key, raw)?) };
+            }
+            "Damage Done" => damage_done = parse_optional_number(key, value.trim_start_matches(','))?,
+            "Damage Possible" => damage_possible = parse_optional_number(key, value.trim_start_matches(','))?,
+            // Seconds, some versions append the unit
+            "Avg TTK" => avg_ttk = parse_optional_number(key, value.trim_start_matches(',').trim_end_matches('s'))?,
+            // Milliseconds
+            "Reaction Time" => {
+                reaction_time = parse_optional_number(key, value.trim_start_matches(',').trim_end_matches("ms"))?;
+            }
+            _ => {}
+        }
+    }
+
+    // Rounded to 2 decimals so versions that log 12345.0 and 12345 agree, and
+    // runs equal after rounding tie in the highscore comparison
+    let score: f64 = score.ok_or("no Score field")?;
+    let score = (score * 100.0).round() / 100.0;
+
+    let accuracy = if hits + misses > 0 {
+        Some((hits as f64 / (hits + misses) as f64 * 100.0 * 100.0).round() / 100.0)
+    } else {
+        None
+    };
+
+    let fov_horizontal_deg = horizontal_fov(fov, &fov_scale, &resolution);
+    let (resolution_width, resolution_height) = match parse_resolution(&resolution) {
+        Some((width, height)) => (Some(width), Some(height)),
+        None => (None, None),
+    };
+
+    let sens_cm = if sens_scale == "cm/360" {
+        Some((horiz_sens, vert_sens))
+    } else {
+        None
+    };
+    // Needs the footer DPI and a scale known to sens_to_cm360
+    let sens_cm_computed = dpi.filter(|&dpi| dpi > 0.0 && horiz_sens > 0.0 && vert_sens > 0.0).and_then(|dpi| {
+        Some((sens_to_cm360(horiz_sens, &sens_scale, dpi)?, sens_to_cm360(vert_sens, &sens_scale, dpi)?))
+    });
+
+    Ok(StatsResult {
+        category: scenario_category(&scenario_name),
+        scenario_name,
+        score,
+        score_scale,
+        kills,
+        hits,
+        misses,
+        accuracy,
+        efficiency: accuracy.map(|accuracy| score * accuracy / 100.0),
+        shots,
+        damage_done,
+        damage_possible,
+        avg_ttk,
+        reaction_time,
+        fov_scale,
+        fov,
+        fov_horizontal_deg,
+        resolution,
+        resolution_width,
+        resolution_height,
+        avg_fps,
+        sens_scale,
+        horiz_sens,
+        vert_sens,
+        sens_ratio: if vert_sens != 0.0 { Some(horiz_sens / vert_sens) } else { None },
+        sens_cm,
+        sens_cm_computed,
+        dpi,
+        date,
+        timestamp,
+        per_bot_accuracy: per_bot_accuracy(&kills_detail),
+        kills_detail,
+        shot_timeline: shot_rows.filter(|rows| !rows.is_empty()).map(|rows| {
+            let start = rows[0].0;
+            rows.into_iter().map(|(timestamp, hit)| ShotEvent { offset: timestamp - start, hit }).collect()
+        }),
+        game_version,
+        crosshair,
+        input_lag,
+        max_fps_config,
+        source_path
+    })
+}
+
+// Width and height from a resolution string like 1920x1080. Also accepts
+// spaces around the x and a trailing refresh rate (2560x1440@144).
+fn parse_resolution(resolution: &str) -> Option<(i32, i32)> {
+    let dimensions = resolution.split('@').next()?;
+    let (width, height) = dimensions.split_once(['x', 'X'])?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+// Converts a KovaaK FOV value to horizontal degrees for the run's aspect ratio.
+// Most scales give a horizontal FOV measured at a reference aspect ratio and
+// scale Hor+ from there; Vertical gives the vertical FOV directly.
+fn horizontal_fov(fov: f64, fov_scale: &str, resolution: &str) -> Option<f64> {
+    let reference_aspect = match fov_scale.to_lowercase().as_str() {
+        "horizontal" => return Some(fov),
+        "vertical" => 1.0,
+        "overwatch" => 16.0 / 9.0,
+        "source" | "cs:go" | "quake" | "4:3 based" => 4.0 / 3.0,
+        _ => return None,
+    };
+
+    let (width, height) = parse_resolution(resolution)?;
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+    let aspect = width as f64 / height as f64;
+
+    let half_fov = (fov / 2.0).to_radians();
+    let horizontal = 2.0 * (half_fov.tan() * aspect / reference_aspect).atan();
+    Some((horizontal.to_degrees() * 100.0).round() / 100.0)
+}
+
+// Degrees turned per mouse count at a sensitivity of 1.0 for the game scales
+// KovaaK offers. Every scale listed here is independent of FOV. Scales not
+// listed (FOV-dependent ones like Battlefield or Fortnite config values, and
+// anything unrecognised) return None, so sens_cm_computed stays None for them.
+fn sens_scale_yaw(sens_scale: &str) -> Option<f64> {
+    match sens_scale.to_lowercase().as_str() {
+        "overwatch" => Some(0.0066),
+        "valorant" => Some(0.07),
+        "cs:go" | "cs2" | "source" | "quake/source" | "apex" | "apex legends" => Some(0.022),
+        _ => None,
+    }
+}
+
+// cm/360 for a sensitivity in the given scale:
+//   cm/360 = 360 / (sens * yaw * dpi) * 2.54
+// where yaw is degrees per count from sens_scale_yaw. cm/360 and inches/360
+// values are already distance based and are returned (converted) directly.
+fn sens_to_cm360(sens: f64, sens_scale: &str, dpi: f64) -> Option<f64> {
+    match sens_scale.to_lowercase().as_str() {
+        "cm/360" => Some(sens),
+        "in/360" | "inches/360" => Some(sens * 2.54),
+        scale => {
+            let yaw = sens_scale_yaw(scale)?;
+            Some(360.0 / (sens * yaw * dpi) * 2.54)
+        }
+    }
+}
+
+fn parse_benchmark_table(json: &str) -> Result<BenchmarkTable, String> {
+    let raw: RawBenchmarkTable =
+        serde_json::from_str(json).map_err(|e| format!("Invalid benchmark table: {}", e))?;
+
+    let mut table = HashMap::new();
+    for categories in raw.into_values() {
+        for subcategories in categories.into_values() {
+            for scenarios in subcategories.into_values() {
+                for (scenario, ranks) in scenarios {
+                    let mut thresholds: Vec<(String, f64)> = ranks.into_iter().collect();
+                    thresholds.sort_by(|a, b| a.1.total_cmp(&b.1));
+                    table.insert(scenario, thresholds);
+                }
+            }
+        }
+    }
+
+    Ok(table)
+}
+
+// Parses a threshold file of the form {"<scenario>": [{"rank": "...", "threshold": 900}, ...]}.
+// Every scenario needs at least one rank, rank names must be unique per
+// scenario and thresholds finite.
+fn parse_threshold_file(json: &str) -> Result<BenchmarkTable, String> {
+    let raw: HashMap<String, Vec<RankThreshold>> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid threshold file: {}", e))?;
+    if raw.is_empty() {
+        return Err("Invalid threshold file: no scenarios".into());
+    }
+
+    let mut table = HashMap::new();
+    for (scenario, ranks) in raw {
+        if ranks.is_empty() {
+            return Err(format!("Invalid threshold file: {} has no ranks", scenario));
+        }
+
+        let mut seen = HashSet::new();
+        for rank in &ranks {
+            if !rank.threshold.is_finite() {
+                return Err(format!("Invalid threshold file: {} {} is not a number", scenario, rank.rank));
+            }
+            if !seen.insert(rank.rank.as_str()) {
+                return Err(format!("Invalid threshold file: {} lists {} twice", scenario, rank.rank));
+            }
+        }
+
+        let mut thresholds: Vec<(String, f64)> =
+            ranks.into_iter().map(|rank| (rank.rank, rank.threshold)).collect();
+        thresholds.sort_by(|a, b| a.1.total_cmp(&b.1));
+        table.insert(scenario, thresholds);
+    }
+
+    Ok(table)
+}
+
+// Splits a VT S5 name into the name without its difficulty and the difficulty's
+// index in VT_DIFFICULTIES, so "VT Pasu Novice S5" and "VT Pasu Advanced S5"
+// share a base name. Names outside the VT S5 pattern give None.
+fn split_vt_name(scenario: &str) -> Option<(&str, usize)> {
+    let name = scenario.trim().strip_suffix(" S5")?;
+    VT_DIFFICULTIES.iter().enumerate().find_map(|(index, difficulty)| {
+        Some((name.strip_suffix(difficulty)?.strip_suffix(' ')?, index))
+    })
+}
+
+fn vt_base_name(scenario: &str) -> Option<&str> {
+    split_vt_name(scenario).map(|(base_name, _)| base_name)
+}
+
+// Energy at the first rank of a difficulty, matching calculateStartingEnergy in
+// the frontend: every rank of the easier difficulties adds ENERGY_INCREMENT.
+fn starting_energy(difficulty_index: usize) -> f64 {
+    ENERGY_BASE + (difficulty_index * RANKS_PER_DIFFICULTY) as f64 * ENERGY_INCREMENT
+}
+
+// Port of calculateScenarioEnergy from the frontend. `thresholds` is sorted
+// ascending and needs at least two ranks to extrapolate the fake ranks below
+// and above the real ones.
+fn scenario_energy(score: f64, thresholds: &[(String, f64)], starting_energy: f64) -> f64 {
+    let lowest = thresholds[0].1;
+    let highest = thresholds[thresholds.len() - 1].1;
+    let fake_lower = lowest - (thresholds[1].1 - lowest);
+    let fake_upper = highest + (highest - thresholds[thresholds.len() - 2].1);
+
+    if score < fake_lower {
+        return score / fake_lower * (starting_energy - ENERGY_INCREMENT);
+    }
+
+    if score < lowest {
+        let progress = (score - fake_lower) / (lowest - fake_lower);
+        return starting_energy - ENERGY_INCREMENT + progress * ENERGY_INCREMENT;
+    }
+
+    let extended: Vec<f64> = thresholds
+        .iter()
+        .map(|(_, threshold)| *threshold)
+        .chain(std::iter::once(fake_upper))
+        .collect();
+
+    for (index, pair) in extended.windows(2).enumerate() {
+        if score >= pair[0] && score < pair[1] {
+            let progress = (score - pair[0]) / (pair[1] - pair[0]);
+            return starting_energy + index as f64 * ENERGY_INCREMENT + progress * ENERGY_INCREMENT;
+        }
+    }
+
+    // Above the fake upper rank
+    starting_energy + thresholds.len() as f64 * ENERGY_INCREMENT
+}
+
+// VT base name -> category, read from the same embedded table as the thresholds
+fn parse_scenario_categories(json: &str) -> Result<HashMap<String, String>, String> {
+    let raw: RawBenchmarkTable =
+        serde_json::from_str(json).map_err(|e| format!("Invalid benchmark table: {}", e))?;
+
+    let mut categories = HashMap::new();
+    for difficulty in raw.into_values() {
+        for (category, subcategories) in difficulty {
+            for scenarios in subcategories.into_values() {
+                for scenario in scenarios.into_keys() {
+                    if let Some(base_name) = vt_base_name(&scenario) {
+                        categories.insert(base_name.to_string(), category.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(categories)
+}
+
+fn scenario_category(scenario: &str) -> Option<String> {
+    static CATEGORIES: OnceLock<HashMap<String, String>> = OnceLock::new();
+    let categories = CATEGORIES.get_or_init(|| {
+        parse_scenario_categories(EMBEDDED_THRESHOLDS).expect("embedded threshold table should be valid")
+    });
+    categories.get(vt_base_name(scenario)?).cloned()
+}
+
+fn rank_info(thresholds: &[(String, f64)], score: f64) -> RankInfo {
+    let reached = thresholds.iter().take_while(|(_, threshold)| score >= *threshold).count();
+    let rank = match reached {
+        0 => "Unranked".to_string(),
+        n => thresholds[n - 1].0.clone(),
+    };
+    let next = thresholds.get(reached);
+
+    RankInfo {
+        rank,
+        next_rank: next.map(|(name, _)| name.clone()),
+        points_to_next: next.map(|(_, threshold)| threshold - score)
+    }
+}
+
+// Library roots listed in libraryfolders.vdf. Current Steam nests each library
+// in a numbered block with a "path" key:
+//     "0"
+//     {
+//         "path"      "C:\\Program Files (x86)\\Steam"
+//         ...
+// Older versions put the path directly on the numbered key ("1" "D:\\Games"),
+// which is only used when there are no "path" keys, since the "apps" blocks of
+// the current format also have numbered keys. VDF escapes backslashes, so they
+// are unescaped here.
+fn parse_library_folders(content: &str) -> Vec<String> {
+    let pairs: Vec<(&str, &str)> = content
+        .lines()
+        .filter_map(|line| {
+            let mut tokens = line.split('"').skip(1).step_by(2);
+            Some((tokens.next()?, tokens.next()?))
+        })
+        .collect();
+
+    let has_path_keys = pairs.iter().any(|(key, _)| *key == "path");
+    pairs
+        .into_iter()
+        .filter(|(key, _)| {
+            if has_path_keys {
+                *key == "path"
+            } else {
+                !key.is_empty() && key.chars().all(|c| c.is_ascii_digit())
+            }
+        })
+        .map(|(_, value)| value.replace("\\\\", "\\"))
+        .collect()
+}
+
+fn get_steam_library_paths(install_path: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let library_file = PathBuf::from(install_path)
+        .join("steamapps")
+        .join("libraryfolders.vdf");
+
+    if let Ok(content) = fs::read_to_string(&library_file) {
+        paths.extend(
+            parse_library_folders(&content)
+                .into_iter()
+                .map(|path| PathBuf::from(path).join("steamapps")),
+        );
+    }
+
+    // Add the default library path as well
+    paths.push(PathBuf::from(install_path).join("steamapps"));
+
+    paths
+}
+
+// steamapps folders of every Steam library on this platform, in priority order.
+#[cfg(target_os = "windows")]
+fn steam_library_candidates() -> Result<Vec<PathBuf>, StatsError> {
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let steam_key = hklm
+        .open_subkey(r"SOFTWARE\WOW6432Node\Valve\Steam")
+        .or_else(|_| hklm.open_subkey(r"SOFTWARE\Valve\Steam"))
+        .map_err(|e| StatsError::SteamNotFound { message: format!("Failed to find Steam registry key: {}", e) })?;
+
+    let install_path: String = steam_key
+        .get_value("InstallPath")
+        .map_err(|e| StatsError::SteamNotFound { message: format!("Failed to get Steam install path: {}", e) })?;
+
+    Ok(get_steam_library_paths(&install_path))
+}
+
+#[cfg(target_os = "linux")]
+fn steam_library_candidates() -> Result<Vec<PathBuf>, StatsError> {
+    let home = std::env::var("HOME")
+        .map_err(|_| StatsError::SteamNotFound { message: "Could not find HOME directory".into() })?;
+    let steam_paths = vec![
+        format!("{}/.local/share/Steam", home),
+        format!("{}/.steam/steam", home),
+        // Flatpak
+        format!("{}/.var/app/com.valvesoftware.Steam/.local/share/Steam", home),
+    ];
+
+    let mut candidates = Vec::new();
+    for base_path in steam_paths {
+        // The default library is pushed last by get_steam_library_paths, so check it first
+        let mut library_paths = get_steam_library_paths(&base_path);
+        library_paths.rotate_right(1);
+        candidates.extend(library_paths);
+    }
+
+    Ok(candidates)
+}
+
+#[cfg(target_os = "macos")]
+fn steam_library_candidates() -> Result<Vec<PathBuf>, StatsError> {
+    let home = std::env::var("HOME")
+        .map_err(|_| StatsError::SteamNotFound { message: "Could not find HOME directory".into() })?;
+    let install_path = format!("{}/Library/Application Support/Steam", home);
+
+    // The default library is pushed last by get_steam_library_paths, so check it first
+    let mut library_paths = get_steam_library_paths(&install_path);
+    library_paths.rotate_right(1);
+
+    Ok(library_paths)
+}
+
+// Stats folders to search on this platform, in priority order: Steam libraries
+// first, then Epic installs. Missing Steam is only an error without Epic.
+fn stats_path_candidates() -> Result<Vec<PathBuf>, StatsError> {
+    let epic = epic_stats_paths();
+    match steam_library_candidates() {
+        Ok(libraries) => Ok(libraries
+            .iter()
+            .flat_map(|library_path| library_stats_paths(library_path))
+            .chain(epic)
+            .collect()),
+        Err(e) if epic.is_empty() => Err(e),
+        Err(_) => Ok(epic),
+    }
+}
+
+// Install folder from one Epic Games launcher manifest (.item JSON), if it is
+// the KovaaK's install. Matched on the display name or the launch executable.
+#[cfg(target_os = "windows")]
+fn parse_epic_manifest(json: &str) -> Option<PathBuf> {
+    let manifest: serde_json::Value = serde_json::from_str(json).ok()?;
+    let field = |key: &str| manifest.get(key).and_then(|value| value.as_str()).unwrap_or_default();
+
+    let is_kovaak = field("DisplayName").to_lowercase().contains("kovaak")
+        || field("LaunchExecutable").starts_with("FPSAimTrainer");
+    let install_location = field("InstallLocation");
+    (is_kovaak && !install_location.is_empty()).then(|| PathBuf::from(install_location))
+}
+
+// Stats folders of KovaaK's installs known to the Epic Games launcher
+#[cfg(target_os = "windows")]
+fn epic_stats_paths() -> Vec<PathBuf> {
+    let program_data = std::env::var_os("ProgramData").unwrap_or_else(|| r"C:\ProgramData".into());
+    let manifests = PathBuf::from(program_data).join(r"Epic\EpicGamesLauncher\Data\Manifests");
+    let Ok(entries) = fs::read_dir(&manifests) else {
+        return Vec::new();
+    };
+
+    let mut manifest_paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("item")))
+        .collect();
+    manifest_paths.sort();
+
+    manifest_paths
+        .iter()
+        .filter_map(|path| parse_epic_manifest(&fs::read_to_string(path).ok()?))
+        .filter_map(|install_dir| {
+            [install_dir.join("FPSAimTrainer/stats"), install_dir.join("stats")]
+                .into_iter()
+                .find(|stats_path| stats_path.is_dir())
+        })
+        .collect()
+}
+
+// The Epic launcher only exists on Windows
+#[cfg(not(target_os = "windows"))]
+fn epic_stats_paths() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+// Stats folders inside one steamapps folder. The default install comes first,
+// then any other common/FPSAimTrainer* folder (beta branches, renamed installs)
+// that actually has a stats folder, in name order.
+fn library_stats_paths(library_path: &Path) -> Vec<PathBuf> {
+    let common = library_path.join("common");
+    let mut paths = vec![common.join("FPSAimTrainer/FPSAimTrainer/stats")];
+
+    let Ok(entries) = fs::read_dir(&common) else {
+        return paths;
+    };
+
+    let mut install_dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                name.starts_with("FPSAimTrainer") && name != "FPSAimTrainer"
+            })
+        })
+        .map(|entry| entry.path())
+        .collect();
+    install_dirs.sort();
+
+    for install_dir in install_dirs {
+        let stats_path = [install_dir.join("FPSAimTrainer/stats"), install_dir.join("stats")]
+            .into_iter()
+            .find(|stats_path| stats_path.is_dir());
+        paths.extend(stats_path);
+    }
+
+    paths
+}
+
+// Stats folder override for portable or sandboxed Steam installs. When set it
+// is used as-is on every platform and discovery never runs.
+fn env_stats_path() -> Option<PathBuf> {
+    std::env::var_os("KOVAAK_STATS_PATH")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+// First stats folder that exists on disk, if any. `extra_paths` are stats
+// folders supplied by the frontend and are tried after the Steam libraries.
+fn find_stats_path(extra_paths: &[String]) -> Result<Option<PathBuf>, StatsError> {
+    Ok(search_stats_path(extra_paths)?.found)
+}
+
+// Every stats folder that exists, without duplicates. Commands that read runs
+// go through all of them, so they agree with get_stats when several Steam
+// libraries have one.
+fn find_stats_paths() -> Result<Vec<PathBuf>, StatsError> {
+    Ok(search_stats_path(&[])?.existing)
+}
+
+// Same as find_stats_path, but also returns every folder that was considered so
+// a failed discovery can show where it looked.
+fn search_stats_path(extra_paths: &[String]) -> Result<StatsPathSearch, StatsError> {
+    if let Some(stats_path) = env_stats_path() {
+        return Ok(StatsPathSearch {
+            found: Some(stats_path.clone()),
+            existing: vec![stats_path.clone()],
+            searched: vec![stats_path]
+        });
+    }
+
+    // The same folder can come up twice, e.g. the Steam root listed in
+    // libraryfolders.vdf and added again as the default library, or reached
+    // through the ~/.steam/steam symlink. Duplicates are dropped by canonical path.
+    let mut seen = HashSet::new();
+    let searched: Vec<PathBuf> = stats_path_candidates()?
+        .into_iter()
+        .chain(extra_paths.iter().map(PathBuf::from))
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .collect();
+    let existing: Vec<PathBuf> = searched.iter().filter(|path| path.exists()).cloned().collect();
+
+    Ok(StatsPathSearch {
+        found: existing.first().cloned(),
+        existing,
+        searched
+    })
+}
+
+// KovaaK stats CSVs, including the .csv.bak copies it leaves behind after a crash
+// and gzipped archives. Extensions match in any case (.CSV, .Csv).
+fn is_stats_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|filename| {
+            let filename = filename.to_ascii_lowercase();
+            filename.ends_with(".csv") || filename.ends_with(".csv.bak") || filename.ends_with(".csv.gz")
+        })
+}
+
+// A stats CSV whose filename starts with one of the requested scenarios. A
+// trailing * is dropped first, so "VT *" tracks every VT scenario.
+fn is_tracked_stats_file(path: &Path, scenarios: &[String]) -> bool {
+    if !is_stats_file(path) {
+        return false;
+    }
+
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|filename| {
+            scenarios
+                .iter()
+                .any(|scenario| filename.starts_with(scenario.strip_suffix('*').unwrap_or(scenario)))
+        })
+}
+
+// Requested entries ending in *, which match by prefix in every match mode
+fn is_wildcard(scenario: &str) -> bool {
+    scenario.ends_with('*')
+}
+
+// Parses every CSV in the stats folder that belongs to one of the requested
+// scenarios. Files that fail to parse are returned separately with the reason.
+fn collect_runs(
+    stats_path: &Path,
+    scenarios: &[String],
+    cache: &StatsCache
+) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts) {
+    collect_matching_runs(stats_path, |path| is_tracked_stats_file(path, scenarios), cache)
+}
+
+// Threads used to parse stats files. KOVAAK_PARSE_THREADS caps how many; when
+// it is unset, 0 or not a number rayon's default of one per logical CPU is used.
+fn parse_pool() -> &'static ThreadPool {
+    static POOL: OnceLock<ThreadPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let threads = std::env::var("KOVAAK_PARSE_THREADS")
+            .ok()
+            .and_then(|threads| threads.trim().parse().ok())
+            .unwrap_or(0);
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to build the stats parse thread pool")
+    })
+}
+
+// Parses the files in the stats folder accepted by `include`. Files are parsed
+// in parallel, but results come back in filename order so callers see the same
+// sequence regardless of directory read order.
+fn collect_matching_runs(
+    stats_path: &Path,
+    include: impl Fn(&Path) -> bool,
+    cache: &StatsCache
+) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts) {
+    scan_stats_folder(stats_path, include, cache, false)
+}
+
+// collect_matching_runs, parsed with parse_footer_only
+fn collect_matching_footers(
+    stats_path: &Path,
+    include: impl Fn(&Path) -> bool,
+    cache: &StatsCache
+) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts) {
+    scan_stats_folder(stats_path, include, cache, true)
+}
+
+fn scan_stats_folder(
+    stats_path: &Path,
+    include: impl Fn(&Path) -> bool,
+    cache: &StatsCache,
+    footer_only: bool
+) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts) {
+    let started = Instant::now();
+    let mut counts = ScanCounts::default();
+    let mut paths = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(stats_path) {
+        for entry in entries.flatten() {
+            counts.scanned_files += 1;
+            let path = entry.path();
+            if include(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    paths.sort();
+    counts.matched_files = paths.len();
+
+    let parsed: Vec<(&PathBuf, Result<StatsResult, String>)> = parse_pool().install(|| {
+        paths
+            .par_iter()
+            .map(|path| (path, cache.parse(path, footer_only)))
+            .collect()
+    });
+
+    let mut runs = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (path, result) in parsed {
+        match result {
+            Ok(stat) => runs.push(stat),
+            Err(reason) => skipped.push(SkippedFile {
+                filename: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                reason
+            }),
+        }
+    }
+    counts.scan_ms = started.elapsed().as_millis() as u64;
+
+    (runs, skipped, counts)
+}
+
+// Runs of exactly one scenario. collect_runs matches by prefix, so runs of
+// longer names sharing the prefix are dropped here.
+fn scenario_runs(stats_paths: &[PathBuf], scenario: &str, cache: &StatsCache) -> Vec<StatsResult> {
+    let scenarios = [scenario.to_string()];
+    let (runs, _, _) = collect_from_folders(stats_paths, |stats_path| collect_runs(stats_path, &scenarios, cache));
+    runs.into_iter()
+        .filter(|stat| stat.scenario_name == scenario)
+        .collect()
+}
+
+// Lowercases and collapses runs of spaces and dashes into one space, so
+// "VT  Pasu-Advanced S5" and "vt pasu advanced s5" compare equal.
+fn normalize_scenario_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// The requested scenario a stats file's scenario name belongs to under `mode`.
+// Fuzzy matching picks the closest requested name. Wildcard entries are never
+// returned, runs they match keep their own name.
+fn match_scenario<'a>(scenario_name: &str, scenarios: &'a [String], mode: MatchMode) -> Option<&'a String> {
+    let mut scenarios = scenarios.iter().filter(|scenario| !is_wildcard(scenario));
+    match mode {
+        MatchMode::Exact => scenarios.find(|scenario| scenario.as_str() == scenario_name),
+        MatchMode::CaseInsensitive => {
+            let normalized = normalize_scenario_name(scenario_name);
+            scenarios.find(|scenario| normalize_scenario_name(scenario) == normalized)
+        }
+        MatchMode::Fuzzy => {
+            let normalized = normalize_scenario_name(scenario_name);
+            scenarios
+                .map(|scenario| (scenario, edit_distance(&normalize_scenario_name(scenario), &normalized)))
+                .filter(|(_, distance)| *distance <= FUZZY_MAX_DISTANCE)
+                .min_by_key(|(_, distance)| *distance)
+                .map(|(scenario, _)| scenario)
+        }
+    }
+}
+
+// Scenario name from a stats filename, see split_stats_filename
+fn path_scenario_name(path: &Path) -> Option<&str> {
+    let filename = path.file_name()?.to_str()?;
+    split_stats_filename(strip_archive_suffix(filename)).ok().map(|(scenario_name, _)| scenario_name)
+}
+
+// Normalized scenario name used to group runs: trimmed, then mapped through
+// the alias table if it has an entry.
+fn grouping_key(scenario_name: &str, aliases: &HashMap<String, String>) -> String {
+    let trimmed = scenario_name.trim();
+    aliases
+        .get(trimmed)
+        .map(|alias| alias.trim())
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+// The requested scenarios plus every old name aliased onto one of them, so runs
+// saved under a previous name are collected too.
+fn alias_sources(scenarios: &[String], aliases: &HashMap<String, String>) -> Vec<String> {
+    let mut sources = scenarios.to_vec();
+    sources.extend(
+        aliases
+            .iter()
+            .filter(|(_, canonical)| scenarios.iter().any(|scenario| scenario.trim() == canonical.trim()))
+            .map(|(old_name, _)| old_name.trim().to_string()),
+    );
+    sources
+}
+
+// Reports an aliased run under its canonical name. Runs without an alias keep
+// their name as written, trimming only affects grouping.
+fn apply_canonical_name(stat: &mut StatsResult, aliases: &HashMap<String, String>) {
+    if !aliases.contains_key(stat.scenario_name.trim()) {
+        return;
+    }
+    let name = grouping_key(&stat.scenario_name, aliases);
+    if name != stat.scenario_name {
+        stat.category = scenario_category(&name);
+        stat.scenario_name = name;
+    }
+}
+
+// Highscore comparison. Exact ties are settled by `tiebreak`, falling back to
+// the later run, so the pick never depends on file order.
+fn is_better_run(candidate: &StatsResult, current: &StatsResult, tiebreak: Tiebreak) -> bool {
+    if candidate.score != current.score {
+        return candidate.score > current.score;
+    }
+
+    if tiebreak == Tiebreak::Accuracy && candidate.accuracy != current.accuracy {
+        return candidate.accuracy > current.accuracy;
+    }
+
+    candidate.date > current.date
+}
+
+// Sort order that puts the better run first, by is_better_run with the
+// default tiebreak
+fn best_first(a: &StatsResult, b: &StatsResult) -> std::cmp::Ordering {
+    if is_better_run(a, b, Tiebreak::default()) {
+        std::cmp::Ordering::Less
+    } else if is_better_run(b, a, Tiebreak::default()) {
+        std::cmp::Ordering::Greater
+    } else {
+        std::cmp::Ordering::Equal
+    }
+}
+
+// Best run per scenario, keyed by grouping_key. Scenarios in `lower_is_better`
+// keep their lowest score instead, equal scores still go through is_better_run.
+fn select_highscores(
+    runs: Vec<StatsResult>,
+    aliases: &HashMap<String, String>,
+    tiebreak: Tiebreak,
+    lower_is_better: &HashSet<String>
+) -> HashMap<String, StatsResult> {
+    let mut scenario_highscores: HashMap<String, StatsResult> = HashMap::new();
+
+    for stat in runs {
+        let key = grouping_key(&stat.scenario_name, aliases);
+        let lower = lower_is_better.contains(&key);
+        let entry = scenario_highscores.entry(key).or_insert(stat.clone());
+        let better = if lower && stat.score != entry.score {
+            stat.score < entry.score
+        } else {
+            is_better_run(&stat, entry, tiebreak)
+        };
+        if better {
+            *entry = stat;
+        }
+    }
+
+    scenario_highscores
+}
+
+fn sort_stats(stats: &mut [StatsResult], sort_by: SortKey, descending: bool) {
+    stats.sort_by(|a, b| {
+        let ordering = match sort_by {
+            SortKey::Name => std::cmp::Ordering::Equal,
+            SortKey::Score => a.score.total_cmp(&b.score),
+            SortKey::Date => a.timestamp.cmp(&b.timestamp).then_with(|| a.date.cmp(&b.date)),
+            SortKey::Accuracy => match (a.accuracy, b.accuracy) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            },
+        }
+        .then_with(|| a.scenario_name.cmp(&b.scenario_name));
+
+        if descending { ordering.reverse() } else { ordering }
+    });
+}
+
+// collect_runs treats an unreadable folder as empty, this says why it was empty
+fn stats_dir_error(stats_path: &Path) -> Option<String> {
+    fs::read_dir(stats_path)
+        .err()
+        .map(|e| format!("Failed to read {}: {}", stats_path.display(), e))
+}
+
+// Runs of the requested scenarios in one folder, matched by `match_mode`.
+// Highscores only need the footer, so the tables aren't parsed.
+fn gather_runs(
+    stats_path: &Path,
+    scenarios: &[String],
+    match_mode: MatchMode,
+    cache: &StatsCache
+) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts) {
+    match match_mode {
+        MatchMode::Exact => {
+            collect_matching_footers(stats_path, |path| is_tracked_stats_file(path, scenarios), cache)
+        }
+        mode => {
+            let wildcards: Vec<String> = scenarios.iter().filter(|scenario| is_wildcard(scenario)).cloned().collect();
+            collect_matching_footers(
+                stats_path,
+                |path| {
+                    is_tracked_stats_file(path, &wildcards)
+                        || (is_stats_file(path)
+                            && path_scenario_name(path).is_some_and(|name| match_scenario(name, scenarios, mode).is_some()))
+                },
+                cache,
+            )
+        }
+    }
+}
+
+// Picks the highscores (and ties, if asked for) out of already gathered runs.
+// Returns (stats, tied_runs), both sorted per the options.
+fn select_from_runs(
+    mut runs: Vec<StatsResult>,
+    scenarios: &[String],
+    options: &HighscoreOptions
+) -> (Vec<StatsResult>, Vec<StatsResult>) {
+    runs.retain(|stat| options.accepts(stat));
+
+    // Loosely matched names group under the scenario they were requested as,
+    // unless an explicit alias already covers them
+    let mut aliases = options.aliases.clone();
+    if options.match_mode != MatchMode::Exact {
+        for stat in &runs {
+            if let Some(scenario) = match_scenario(&stat.scenario_name, scenarios, options.match_mode) {
+                aliases
+                    .entry(stat.scenario_name.trim().to_string())
+                    .or_insert_with(|| scenario.clone());
+            }
+        }
+    }
+
+    let tie_candidates = if options.include_ties { runs.clone() } else { Vec::new() };
+    let highscores = select_highscores(runs, &aliases, options.tiebreak, &options.lower_is_better);
+
+    let mut tied_runs: Vec<StatsResult> = tie_candidates
+        .into_iter()
+        .filter(|stat| {
+            highscores
+                .get(&grouping_key(&stat.scenario_name, &aliases))
+                .is_some_and(|best| best.score == stat.score && best.source_path != stat.source_path)
+        })
+        .collect();
+    sort_stats(&mut tied_runs, options.sort_by, options.descending);
+
+    let mut stats: Vec<StatsResult> = highscores.into_values().collect();
+    for stat in stats.iter_mut().chain(tied_runs.iter_mut()) {
+        apply_canonical_name(stat, &aliases);
+    }
+    sort_stats(&mut stats, options.sort_by, options.descending);
+
+    (stats, tied_runs)
+}
+
+// Builds the highscore result for one stats folder. Every get_stats variant goes
+// through here so the selection stays the same across platforms and sources.
+fn collect_highscores(
+    stats_path: &Path,
+    scenarios: &[String],
+    options: &HighscoreOptions,
+    cache: &StatsCache
+) -> PathResult {
+    if !stats_path.exists() {
+        return PathResult {
+            stats_path: stats_path.to_string_lossy().into_owned(),
+            exists: false,
+            stats: Vec::new(),
+            skipped: Vec::new(),
+            searched_paths: Vec::new(),
+            tied_runs: Vec::new(),
+            error: None,
+            scanned_files: 0,
+            matched_files: 0,
+            scan_ms: 0
+        };
+    }
+
+    let sources = alias_sources(scenarios, &options.aliases);
+    let (runs, skipped, counts) = gather_runs(stats_path, &sources, options.match_mode, cache);
+    let (stats, tied_runs) = select_from_runs(runs, scenarios, options);
+
+    PathResult {
+        stats_path: stats_path.to_string_lossy().into_owned(),
+        exists: true,
+        stats,
+        skipped,
+        searched_paths: Vec::new(),
+        tied_runs,
+        error: stats_dir_error(stats_path),
+        scanned_files: counts.scanned_files,
+        matched_files: counts.matched_files,
+        scan_ms: counts.scan_ms
+    }
+}
+
+// Highscores across several stats folders, e.g. one synced from another
+// machine. The same file in more than one folder (same name and score) only
+// counts once. stats_path lists the folders as "merged: a; b".
+// Runs from every folder in `stats_paths` that exists. A file found in more
+// than one of them (same file name and score) is only kept once, and the scan
+// counts are summed.
+fn collect_from_folders(
+    stats_paths: &[PathBuf],
+    collect: impl Fn(&Path) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts)
+) -> (Vec<StatsResult>, Vec<SkippedFile>, ScanCounts) {
+    let mut runs = Vec::new();
+    let mut skipped = Vec::new();
+    let mut seen_runs = HashSet::new();
+    let mut seen_skipped = HashSet::new();
+    let mut counts = ScanCounts::default();
+
+    for stats_path in stats_paths.iter().filter(|stats_path| stats_path.exists()) {
+        let (folder_runs, folder_skipped, folder_counts) = collect(stats_path);
+        counts.scanned_files += folder_counts.scanned_files;
+        counts.matched_files += folder_counts.matched_files;
+        counts.scan_ms += folder_counts.scan_ms;
+        for stat in folder_runs {
+            let filename = Path::new(&stat.source_path).file_name().map(|name| name.to_os_string());
+            if seen_runs.insert((filename, stat.score.to_bits())) {
+                runs.push(stat);
+            }
+        }
+        skipped.extend(folder_skipped.into_iter().filter(|file| seen_skipped.insert(file.filename.clone())));
+    }
+
+    (runs, skipped, counts)
+}
+
+// Names of the stats files in `stats_paths`, each name once
+fn stats_file_names(stats_paths: &[PathBuf]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    stats_paths
+        .iter()
+        .filter_map(|stats_path| fs::read_dir(stats_path).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .filter(|path| is_stats_file(path))
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .filter(|filename| seen.insert(filename.clone()))
+        .collect()
+}
+
+// stats_path reported for a result read from `stats_paths`
+fn folders_label(stats_paths: &[PathBuf]) -> String {
+    match stats_paths {
+        [stats_path] => stats_path.to_string_lossy().into_owned(),
+        _ => {
+            let folders: Vec<String> = stats_paths.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+            format!("merged: {}", folders.join("; "))
+        }
+    }
+}
+
+// First read error of a folder in `stats_paths` that exists
+fn folders_error(stats_paths: &[PathBuf]) -> Option<String> {
+    stats_paths
+        .iter()
+        .filter(|stats_path| stats_path.exists())
+        .find_map(|stats_path| stats_dir_error(stats_path))
+}
+
+fn collect_merged_highscores(
+    stats_paths: &[PathBuf],
+    scenarios: &[String],
+    options: &HighscoreOptions,
+    cache: &StatsCache
+) -> PathResult {
+    let sources = alias_sources(scenarios, &options.aliases);
+    let (runs, skipped, counts) = collect_from_folders(stats_paths, |stats_path| {
+        gather_runs(stats_path, &sources, options.match_mode, cache)
+    });
+    let (stats, tied_runs) = select_from_runs(runs, scenarios, options);
+
+    PathResult {
+        stats_path: folders_label(stats_paths),
+        exists: stats_paths.iter().any(|stats_path| stats_path.exists()),
+        stats,
+        skipped,
+        searched_paths: Vec::new(),
+        tied_runs,
+        error: folders_error(stats_paths),
+        scanned_files: counts.scanned_files,
+        matched_files: counts.matched_files,
+        scan_ms: counts.scan_ms
+    }
+}
+
+// Parses the tracked runs straight out of a zip of stats files, without
+// extracting anything. Entries in subfolders are read too. Runs get
+// "<zip>!<entry>" as their source_path.
+fn collect_zip_runs(
+    zip_path: &Path,
+    scenarios: &[String]
+) -> Result<(Vec<StatsResult>, Vec<SkippedFile>, ScanCounts), String> {
+    let started = Instant::now();
+    let zip_error = |e: ZipError| match e {
+        ZipError::UnsupportedArchive(reason) if reason == ZipError::PASSWORD_REQUIRED => {
+            format!("{} is password protected", zip_path.display())
+        }
+        e => format!("{} is not a readable zip archive: {}", zip_path.display(), e),
+    };
+
+    let file = fs::File::open(zip_path)
+        .map_err(|e| format!("Failed to open {}: {}", zip_path.display(), e))?;
+    let mut archive = ZipArchive::new(file).map_err(zip_error)?;
+    let source = canonical_path_string(zip_path);
+
+    let mut counts = ScanCounts::default();
+    let mut runs = Vec::new();
+    let mut skipped = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        counts.scanned_files += 1;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let entry_name = entry.name().to_string();
+        let filename = entry_name.rsplit(['/', '\\']).next().unwrap_or(&entry_name).to_string();
+        if strip_suffix_ignore_case(&filename, ".csv").is_none()
+            || !is_tracked_stats_file(Path::new(&filename), scenarios)
+        {
+            continue;
+        }
+        counts.matched_files += 1;
+
+        let mut skip = |reason: String| skipped.push(SkippedFile { filename: filename.clone(), reason });
+        if entry.size() > MAX_STATS_FILE_SIZE {
+            skip("file too large".into());
+            continue;
+        }
+        let mut bytes = Vec::new();
+        if let Err(e) = (&mut entry).take(MAX_STATS_FILE_SIZE + 1).read_to_end(&mut bytes) {
+            return Err(format!("{} is corrupt: {}", zip_path.display(), e));
+        }
+        if bytes.len() as u64 > MAX_STATS_FILE_SIZE {
+            skip("file too large".into());
+            continue;
+        }
+        let Ok(content) = String::from_utf8(bytes) else {
+            skip("unreadable".into());
+            continue;
+        };
+
+        match parse_csv_content(&content, &filename) {
+            Ok(mut stat) => {
+                stat.source_path = format!("{}!{}", source, entry_name);
+                runs.push(stat);
+            }
+            Err(reason) => skip(reason),
+        }
+    }
+    counts.scan_ms = started.elapsed().as_millis() as u64;
+
+    Ok((runs, skipped, counts))
+}
+
+// Highscores from the discovered stats folder with default options, for
+// commands that build on what get_stats returns. Merged like get_stats when
+// more than one stats folder exists.
+fn discovered_highscores(scenarios: &[String], cache: &StatsCache) -> Result<PathResult, String> {
+    let search = search_stats_path(&[])?;
+    let options = HighscoreOptions::default();
+    Ok(match search.found {
+        Some(_) if search.existing.len() > 1 => {
+            collect_merged_highscores(&search.existing, scenarios, &options, cache)
+        }
+        Some(stats_path) => collect_highscores(&stats_path, scenarios, &options, cache),
+        None => missing_stats_result(),
+    })
+}
+
+fn missing_stats_result() -> PathResult {
+    PathResult {
+        stats_path: "No stats path found".into(),
+        exists: false,
+        stats: Vec::new(),
+        skipped: Vec::new(),
+        searched_paths: Vec::new(),
+        tied_runs: Vec::new(),
+        error: None,
+        scanned_files: 0,
+        matched_files: 0,
+        scan_ms: 0
+    }
+}
+
+// The stats folder is picked in this order: the KOVAAK_STATS_PATH environment
+// variable, then `custom_path`, then `stats_paths` (merged into one result),
+// then Steam discovery (plus `extra_paths`). Discovery merges every folder it
+// finds, not just the first.
+// `aliases` maps old scenario names onto a canonical name; runs of the old
+// names are collected and compete with the canonical one, and are reported
+// under the canonical name. Names are also trimmed before grouping, and loosely
+// matched runs are reported under the name they were requested as. Entries
+// ending in * match every scenario with that prefix. `tiebreak` picks
+// between runs with the same score and defaults to the latest run. `from` and
+// `to` are inclusive ISO dates; runs outside them are ignored. `match_mode`
+// loosens how requested names match stats files and defaults to exact. Runs
+// below `min_fps` can't be the highscore. The stats come back sorted by
+// `sort_by` (name by default), ascending unless `descending` is set. With
+// `include_ties`, other runs that equal a best score are listed in tied_runs.
+// Runs are parsed footer-only, so kills_detail, per_bot_accuracy and
+// shot_timeline are empty here; get_stats_history has them.
+// Scenarios in `lower_is_better` (matched after aliasing) keep their lowest
+// score, e.g. time trials. Every Voltaic S5 benchmark scenario is scored
+// higher-is-better, so the benchmark itself never needs it. `season_start` is
+// an ISO date like `from`; only runs on or after both count.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn get_stats(
+    cache: tauri::State<StatsCache>,
+    scenarios: Vec<String>,
+    custom_path: Option<String>,
+    extra_paths: Option<Vec<String>>,
+    aliases: Option<HashMap<String, String>>,
+    tiebreak: Option<Tiebreak>,
+    from: Option<String>,
+    to: Option<String>,
+    match_mode: Option<MatchMode>,
+    min_fps: Option<f64>,
+    sort_by: Option<SortKey>,
+    descending: Option<bool>,
+    include_ties: Option<bool>,
+    stats_paths: Option<Vec<String>>,
+    lower_is_better: Option<HashSet<String>>,
+    season_start: Option<String>
+) -> Result<PathResult, StatsError> {
+    let from = from
+        .map(|from| parse_iso_bound(&from, false))
+        .transpose()
+        .map_err(|message| StatsError::InvalidDate { message })?;
+    let season_start = season_start
+        .map(|season_start| parse_iso_bound(&season_start, false))
+        .transpose()
+        .map_err(|message| StatsError::InvalidDate { message })?;
+
+    let options = HighscoreOptions {
+        aliases: aliases.unwrap_or_default(),
+        tiebreak: tiebreak.unwrap_or_default(),
+        // The later of the two lower bounds wins
+        from: from.max(season_start),
+        to: to
+            .map(|to| parse_iso_bound(&to, true))
+            .transpose()
+            .map_err(|message| StatsError::InvalidDate { message })?,
+        match_mode: match_mode.unwrap_or_default(),
+        min_fps,
+        sort_by: sort_by.unwrap_or_default(),
+        descending: descending.unwrap_or_default(),
+        include_ties: include_ties.unwrap_or_default(),
+        lower_is_better: lower_is_better.unwrap_or_default()
+    };
+
+    let stats_paths: Vec<PathBuf> = stats_paths.unwrap_or_default().into_iter().map(PathBuf::from).collect();
+    if env_stats_path().is_none() && custom_path.is_none() && !stats_paths.is_empty() {
+        return Ok(collect_merged_highscores(&stats_paths, &scenarios, &options, &cache));
+    }
+
+    let (stats_path, searched) = match env_stats_path().or(custom_path.map(PathBuf::from)) {
+        Some(stats_path) => (stats_path, Vec::new()),
+        None => {
+            let search = search_stats_path(&extra_paths.unwrap_or_default())?;
+            let searched: Vec<String> = search
+                .searched
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            match search.found {
+                // Several libraries with a stats folder (e.g. a moved install
+                // that left the old one behind) are merged
+                Some(_) if search.existing.len() > 1 => {
+                    let mut result = collect_merged_highscores(&search.existing, &scenarios, &options, &cache);
+                    result.searched_paths = searched;
+                    return Ok(result);
+                }
+                Some(stats_path) => (stats_path, searched),
+                None => {
+                    return Err(StatsError::StatsFolderNotFound {
+                        message: "No KovaaK's stats folder found in any Steam library".into(),
+                        searched_paths: searched
+                    })
+                }
+            }
+        }
+    };
+
+    // exists() is false for any error, so a folder whose parent can't be
+    // accessed would look missing. A folder that exists but can't be listed is
+    // reported in result.error instead.
+    if let Err(e) = fs::metadata(&stats_path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(StatsError::Io { message: format!("Failed to access {}: {}", stats_path.display(), e) });
+        }
+    }
+
+    let mut result = collect_highscores(&stats_path, &scenarios, &options, &cache);
+    result.searched_paths = searched;
+    Ok(result)
+}
+
+// Runs discovery only and reports where the stats folder is, without parsing
+// anything. A failed discovery is a normal result here, with searched_paths
+// listing where it looked.
+#[tauri::command]
+fn locate_stats() -> Result<PathResult, StatsError> {
+    let search = search_stats_path(&[])?;
+    let searched_paths = search
+        .searched
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+
+    let mut result = match search.found {
+        Some(stats_path) => PathResult {
+            stats_path: stats_path.to_string_lossy().into_owned(),
+            exists: stats_path.exists(),
+            stats: Vec::new(),
+            skipped: Vec::new(),
+            searched_paths: Vec::new(),
+            tied_runs: Vec::new(),
+            error: None,
+            scanned_files: 0,
+            matched_files: 0,
+            scan_ms: 0
+        },
+        None => missing_stats_result(),
+    };
+    result.searched_paths = searched_paths;
+
+    Ok(result)
+}
+
+// Every run for the requested scenarios, oldest first. The KovaaK date format
+// (YYYY.MM.DD-HH.MM.SS) sorts chronologically as a string. `aliases` works as
+// in get_stats: runs of old names are included under the canonical name.
+#[tauri::command]
+fn get_stats_history(
+    cache: tauri::State<StatsCache>,
+    scenarios: Vec<String>,
+    aliases: Option<HashMap<String, String>>
+) -> Result<PathResult, String> {
+    let stats_paths = find_stats_paths()?;
+    if stats_paths.is_empty() {
+        return Ok(missing_stats_result());
+    }
+
+    let aliases = aliases.unwrap_or_default();
+    let sources = alias_sources(&scenarios, &aliases);
+    let (mut stats, skipped, counts) =
+        collect_from_folders(&stats_paths, |stats_path| collect_runs(stats_path, &sources, &cache));
+    for stat in &mut stats {
+        apply_canonical_name(stat, &aliases);
+    }
+    stats.sort_by(|a, b| a.date.cmp(&b.date));
+
+    Ok(PathResult {
+        stats_path: folders_label(&stats_paths),
+        exists: true,
+        stats,
+        skipped,
+        searched_paths: Vec::new(),
+        tied_runs: Vec::new(),
+        error: folders_error(&stats_paths),
+        scanned_files: counts.scanned_files,
+        matched_files: counts.matched_files,
+        scan_ms: counts.scan_ms
+    })
+}
+
+// Highscores from a zip of stats files, e.g. a shared benchmark attempt, picked
+// with the default get_stats options. Corrupt or password protected archives
+// are an error.
+#[tauri::command]
+fn get_stats_from_zip(zip_path: String, scenarios: Vec<String>) -> Result<PathResult, String> {
+    let path = PathBuf::from(&zip_path);
+    if !path.is_file() {
+        return Err(format!("Zip file not found: {}", zip_path));
+    }
+
+    let (runs, skipped, counts) = collect_zip_runs(&path, &scenarios)?;
+    let (stats, tied_runs) = select_from_runs(runs, &scenarios, &HighscoreOptions::default());
+
+    Ok(PathResult {
+        stats_path: zip_path,
+        exists: true,
+        stats,
+        skipped,
+        searched_paths: Vec::new(),
+        tied_runs,
+        error: None,
+        scanned_files: counts.scanned_files,
+        matched_files: counts.matched_files,
+        scan_ms: counts.scan_ms
+    })
+}
+
+// Every scenario with at least one stats file, sorted by name, with its run count.
+#[tauri::command]
+fn list_scenarios() -> Result<Vec<ScenarioInfo>, String> {
+    let mut play_counts: HashMap<String, u32> = HashMap::new();
+
+    for filename in stats_file_names(&find_stats_paths()?) {
+        if let Ok((name, _)) = split_stats_filename(strip_archive_suffix(&filename)) {
+            *play_counts.entry(name.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    let mut scenarios: Vec<ScenarioInfo> = play_counts
+        .into_iter()
+        .map(|(name, play_count)| ScenarioInfo { name, play_count })
+        .collect();
+    scenarios.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(scenarios)
+}
+
+// Drops every cached parse so the next scan re-reads all files.
+#[tauri::command]
+fn clear_stats_cache(cache: tauri::State<StatsCache>) {
+    cache.0.lock().unwrap().clear();
+}
+
+// Watches the stats folder and emits a `new-run` event with the parsed run
+// whenever a tracked scenario's CSV appears. Each file is emitted once, after
+// its writes have settled and it parses.
+#[tauri::command]
+fn start_watching(
+    app: AppHandle,
+    watcher: tauri::State<StatsWatcher>,
+    scenarios: Vec<String>
+) -> Result<String, String> {
+    let stats_paths = find_stats_paths()?;
+    if stats_paths.is_empty() {
+        return Err("No stats path found".into());
+    }
+    let (changed, changes) = mpsc::channel::<PathBuf>();
+
+    let mut stats_watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+
+        for path in event.paths.into_iter().filter(|path| is_tracked_stats_file(path, &scenarios)) {
+            let _ = changed.send(path);
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    // KovaaK writes the footer in several steps, and Score comes before the
+    // settings. A file is only parsed once it has gone WATCH_SETTLE without
+    // events, so a half-written run is never emitted. Dropping the watcher
+    // drops the sender, which ends this thread.
+    std::thread::spawn(move || {
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+        let mut emitted: HashSet<PathBuf> = HashSet::new();
+        loop {
+            match changes.recv_timeout(WATCH_POLL) {
+                Ok(path) if !emitted.contains(&path) => {
+                    pending.insert(path, Instant::now());
+                }
+                Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            let settled: Vec<PathBuf> = pending
+                .iter()
+                .filter(|(_, last_change)| last_change.elapsed() >= WATCH_SETTLE)
+                .map(|(path, _)| path.clone())
+                .collect();
+            for path in settled {
+                pending.remove(&path);
+                if let Ok(stat) = parse_csv_file(&path) {
+                    if let Err(err) = app.emit("new-run", &stat) {
+                        println!("Failed to emit new-run event: {}", err);
+                    }
+                    emitted.insert(path);
+                }
+            }
+        }
+    });
+
+    for stats_path in &stats_paths {
+        stats_watcher
+            .watch(stats_path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", stats_path.display(), e))?;
+    }
+
+    // Replacing a previous watcher drops it, which stops it
+    *watcher.0.lock().unwrap() = Some(stats_watcher);
+
+    Ok(folders_label(&stats_paths))
+}
+
+#[tauri::command]
+fn stop_watching(watcher: tauri::State<StatsWatcher>) {
+    watcher.0.lock().unwrap().take();
+}
+
+// Converts a KovaaK sensitivity to cm/360 using sens_to_cm360. DPI defaults to 800.
+// There's no fov parameter: every scale sens_scale_yaw knows turns the same at
+// any FOV, and FOV-dependent scales get the unsupported error instead of a
+// guessed formula.
+#[tauri::command]
+fn convert_sens(horiz: f64, sens_scale: String, dpi: Option<f64>) -> Result<f64, String> {
+    let dpi = dpi.unwrap_or(800.0);
+    if horiz <= 0.0 || dpi <= 0.0 {
+        return Err("Sensitivity and DPI must be greater than zero".into());
+    }
+
+    sens_to_cm360(horiz, &sens_scale, dpi)
+        .ok_or_else(|| format!("Unsupported sensitivity scale: {}", sens_scale))
+}
+
+// Rank reached by `score` on a benchmark scenario, None if it isn't in the table.
+#[tauri::command]
+fn rank_for_score(thresholds: tauri::State<ThresholdTable>, scenario: String, score: f64) -> Option<RankInfo> {
+    thresholds
+        .0
+        .lock()
+        .unwrap()
+        .get(&scenario)
+        .map(|thresholds| rank_info(thresholds, score))
+}
+
+// Scenarios whose best run was set on a game version older than `min_version`,
+// or without a readable version, sorted by name. Unplayed scenarios are left out.
+#[tauri::command]
+fn flag_outdated_runs(
+    cache: tauri::State<StatsCache>,
+    scenarios: Vec<String>,
+    min_version: String
+) -> Result<Vec<OutdatedRun>, String> {
+    let min = parse_game_version(&min_version).ok_or_else(|| format!("Invalid game version: {}", min_version))?;
+
+    let mut outdated: Vec<OutdatedRun> = discovered_highscores(&scenarios, &cache)?
+        .stats
+        .into_iter()
+        .filter_map(|stat| {
+            let reason = match stat.game_version.as_deref().and_then(parse_game_version) {
+                Some(version) if compare_game_versions(&version, &min).is_lt() => {
+                    format!("older than {}", min_version)
+                }
+                Some(_) => return None,
+                None => "unknown version".to_string(),
+            };
+            Some(OutdatedRun {
+                scenario_name: stat.scenario_name,
+                game_version: stat.game_version,
+                reason
+            })
+        })
+        .collect();
+    outdated.sort_by(|a, b| a.scenario_name.cmp(&b.scenario_name));
+
+    Ok(outdated)
+}
+
+// How many of `scenarios` are currently at each rank, from their best score.
+// Unplayed scenarios, ones below the first threshold and ones the threshold
+// table doesn't have all count as "Unranked", the name rank_info uses.
+#[tauri::command]
+fn rank_distribution(
+    cache: tauri::State<StatsCache>,
+    thresholds: tauri::State<ThresholdTable>,
+    scenarios: Vec<String>
+) -> Result<HashMap<String, u32>, String> {
+    let best_scores: HashMap<String, f64> = discovered_highscores(&scenarios, &cache)?
+        .stats
+        .into_iter()
+        .map(|stat| (stat.scenario_name, stat.score))
+        .collect();
+
+    let table = thresholds.0.lock().unwrap();
+    let mut distribution: HashMap<String, u32> = HashMap::new();
+    for scenario in &scenarios {
+        let rank = match (table.get(scenario), best_scores.get(scenario)) {
+            (Some(thresholds), Some(&score)) => rank_info(thresholds, score).rank,
+            _ => "Unranked".to_string(),
+        };
+        *distribution.entry(rank).or_insert(0) += 1;
+    }
+
+    Ok(distribution)
+}
+
+// Replaces the threshold table used by the rank and energy commands with the
+// one in `path` and returns how many scenarios it has. The current table is
+// kept when the file is invalid.
+#[tauri::command]
+fn load_benchmark_table(thresholds: tauri::State<ThresholdTable>, path: String) -> Result<usize, String> {
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let table = parse_threshold_file(&json)?;
+    let count = table.len();
+
+    *thresholds.0.lock().unwrap() = table;
+    Ok(count)
+}
+
+// Replaces the threshold table with the benchmark gist the frontend fetched,
+// in its difficulty -> category -> subcategory layout. Returns the number of
+// scenarios loaded.
+#[tauri::command]
+fn set_benchmark_table(thresholds: tauri::State<ThresholdTable>, json: String) -> Result<usize, String> {
+    let table = parse_benchmark_table(&json)?;
+    if table.is_empty() {
+        return Err("Invalid benchmark table: no scenarios".into());
+    }
+    let count = table.len();
+
+    *thresholds.0.lock().unwrap() = table;
+    Ok(count)
+}
+
+// Trailing simple moving average of `scores`. The first window - 1 points
+// average over the runs available so far, so the output matches the input length.
+fn moving_average(scores: &[f64], window: usize) -> Vec<f64> {
+    let mut sum = 0.0;
+    scores
+        .iter()
+        .enumerate()
+        .map(|(i, score)| {
+            sum += score;
+            if i >= window {
+                sum -= scores[i - window];
+            }
+            sum / (i + 1).min(window) as f64
+        })
+        .collect()
+}
+
+// One scenario's history, oldest first, with a moving average over `window`
+// runs. The window is clamped to between 1 and the number of runs.
+#[tauri::command]
+fn get_stats_history_smoothed(
+    cache: tauri::State<StatsCache>,
+    scenario: String,
+    window: usize
+) -> Result<SmoothedHistory, String> {
+    let mut runs = scenario_runs(&find_stats_paths()?, &scenario, &cache);
+    runs.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let window = window.clamp(1, runs.len().max(1));
+    let scores: Vec<f64> = runs.iter().map(|stat| stat.score).collect();
+
+    Ok(SmoothedHistory {
+        moving_average: moving_average(&scores, window),
+        runs,
+        window
+    })
+}
+
+// Summary statistics over every run of one scenario, None if it was never played.
+#[tauri::command]
+fn scenario_summary(
+    cache: tauri::State<StatsCache>,
+    scenario: String
+) -> Result<Option<ScenarioSummary>, String> {
+    let stats_paths = find_stats_paths()?;
+
+    let runs = scenario_runs(&stats_paths, &scenario, &cache);
+
+    let Some(best) = runs.iter().reduce(|best, stat| if is_better_run(stat, best, Tiebreak::default()) { stat } else { best }) else {
+        return Ok(None);
+    };
+
+    let mut scores: Vec<f64> = runs.iter().map(|stat| stat.score).collect();
+    scores.sort_by(|a, b| a.total_cmp(b));
+
+    let run_count = scores.len();
+    let average_score = scores.iter().sum::<f64>() / run_count as f64;
+    // Same index twice for odd counts, the two middle values for even counts
+    let median_score = (scores[(run_count - 1) / 2] + scores[run_count / 2]) / 2.0;
+    let variance = scores.iter().map(|score| (score - average_score).powi(2)).sum::<f64>() / run_count as f64;
+
+    Ok(Some(ScenarioSummary {
+        run_count,
+        best_score: best.score,
+        average_score,
+        median_score,
+        std_dev: variance.sqrt(),
+        best_date: best.date.clone()
+    }))
+}
+
+// Weighted sum of per-scenario energy. Each scenario's best score is turned into
+// energy with the loaded thresholds, the same way the frontend computes it,
+// and multiplied by its weight. Unplayed scenarios count as a score of 0.
+#[tauri::command]
+fn overall_benchmark_score(
+    cache: tauri::State<StatsCache>,
+    thresholds: tauri::State<ThresholdTable>,
+    scenarios_with_weights: HashMap<String, f64>
+) -> Result<OverallBenchmarkScore, String> {
+    let scenarios: Vec<String> = scenarios_with_weights.keys().cloned().collect();
+    let best_scores: HashMap<String, f64> = discovered_highscores(&scenarios, &cache)?
+        .stats
+        .into_iter()
+        .map(|stat| (stat.scenario_name, stat.score))
+        .collect();
+
+    let table = thresholds.0.lock().unwrap();
+    let mut contributions = Vec::new();
+    let mut missing_thresholds = Vec::new();
+
+    for (scenario, weight) in scenarios_with_weights {
+        let thresholds = table.get(&scenario).filter(|thresholds| thresholds.len() >= 2);
+        let (Some(thresholds), Some((_, difficulty_index))) = (thresholds, split_vt_name(&scenario)) else {
+            missing_thresholds.push(scenario);
+            continue;
+        };
+
+        let best_score = best_scores.get(&scenario).copied();
+        let energy = scenario_energy(best_score.unwrap_or(0.0), thresholds, starting_energy(difficulty_index));
+
+        contributions.push(ScenarioEnergy {
+            scenario_name: scenario,
+            best_score,
+            energy,
+            weight,
+            weighted_energy: energy * weight
+        });
+    }
+
+    contributions.sort_by(|a, b| a.scenario_name.cmp(&b.scenario_name));
+    missing_thresholds.sort();
+
+    Ok(OverallBenchmarkScore {
+        overall_energy: contributions.iter().map(|contribution| contribution.weighted_energy).sum(),
+        contributions,
+        missing_thresholds
+    })
+}
+
+// The latest `limit` runs across every scenario, newest first. Runs without a
+// parsed timestamp sort last. Parsed footer-only, like get_stats.
+#[tauri::command]
+fn recent_runs(cache: tauri::State<StatsCache>, limit: usize) -> Result<Vec<StatsResult>, String> {
+    let (mut runs, _, _) = collect_from_folders(&find_stats_paths()?, |stats_path| {
+        collect_matching_footers(stats_path, is_stats_file, &cache)
+    });
+    runs.sort_by_key(|stat| std::cmp::Reverse(stat.timestamp));
+    runs.truncate(limit);
+
+    Ok(runs)
+}
+
+// Scenarios whose all-time best run was set after `timestamp` (Unix seconds),
+// sorted by name. Bests without a parsed date are never reported.
+#[tauri::command]
+fn pbs_since(
+    cache: tauri::State<StatsCache>,
+    timestamp: i64,
+    scenarios: Vec<String>
+) -> Result<Vec<PersonalBest>, String> {
+    let mut pbs: Vec<PersonalBest> = discovered_highscores(&scenarios, &cache)?
+        .stats
+        .into_iter()
+        .filter_map(|stat| {
+            let run_timestamp = stat.timestamp.filter(|run_timestamp| *run_timestamp > timestamp)?;
+            Some(PersonalBest {
+                scenario_name: stat.scenario_name,
+                score: stat.score,
+                timestamp: run_timestamp
+            })
+        })
+        .collect();
+    pbs.sort_by(|a, b| a.scenario_name.cmp(&b.scenario_name));
+
+    Ok(pbs)
+}
+
+// Number of runs per day (YYYY-MM-DD) between `from` and `to` (inclusive Unix
+// seconds), for one scenario or all of them. Days without runs are left out.
+// Scenario and date both come from the file name, so no file is opened.
+#[tauri::command]
+fn play_calendar(scenario: Option<String>, from: i64, to: i64) -> Result<HashMap<String, u32>, String> {
+    let timestamps: Vec<i64> = stats_file_names(&find_stats_paths()?)
+        .iter()
+        .filter_map(|filename| {
+            let (scenario_name, date) = split_stats_filename(strip_archive_suffix(filename)).ok()?;
+            if scenario.as_deref().is_some_and(|scenario| scenario != scenario_name) {
+                return None;
+            }
+            parse_kovaak_date(date)
+        })
+        .collect();
+
+    let mut calendar: HashMap<String, u32> = HashMap::new();
+    for timestamp in timestamps {
+        if timestamp < from || timestamp > to {
+            continue;
+        }
+        if let Some(datetime) = Local.timestamp_opt(timestamp, 0).single() {
+            *calendar.entry(datetime.date_naive().to_string()).or_insert(0) += 1;
+        }
+    }
+
+    Ok(calendar)
+}
+
+// Highest score per day (YYYY-MM-DD) one scenario was played, sorted by date.
+// Runs without a parsed timestamp are left out.
+#[tauri::command]
+fn daily_bests(cache: tauri::State<StatsCache>, scenario: String) -> Result<Vec<(String, f64)>, String> {
+    let stats_paths = find_stats_paths()?;
+
+    let mut bests: HashMap<String, f64> = HashMap::new();
+    for stat in scenario_runs(&stats_paths, &scenario, &cache) {
+        // Same local-day reading of the timestamp as play_calendar
+        let Some(datetime) = stat.timestamp.and_then(|timestamp| Local.timestamp_opt(timestamp, 0).single()) else {
+            continue;
+        };
+        let best = bests.entry(datetime.date_naive().to_string()).or_insert(stat.score);
+        *best = best.max(stat.score);
+    }
+
+    let mut bests: Vec<(String, f64)> = bests.into_iter().collect();
+    bests.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(bests)
+}
+
+// The `n` best runs of one scenario, best first. Copies of the same run (same
+// date and score, e.g. a backup next to its .gz) are only counted once.
+#[tauri::command]
+fn top_runs(cache: tauri::State<StatsCache>, scenario: String, n: usize) -> Result<Vec<StatsResult>, String> {
+    let stats_paths = find_stats_paths()?;
+
+    let mut seen = HashSet::new();
+    let mut runs: Vec<StatsResult> = scenario_runs(&stats_paths, &scenario, &cache)
+        .into_iter()
+        .filter(|stat| seen.insert((stat.date.clone(), stat.score.to_bits())))
+        .collect();
+    runs.sort_by(best_first);
+    runs.truncate(n);
+
+    Ok(runs)
+}
+
+// How many of `scenarios` have at least one run that parsed. Scenarios whose
+// files all failed to parse count as missing.
+#[tauri::command]
+fn benchmark_progress(
+    cache: tauri::State<StatsCache>,
+    scenarios: Vec<String>
+) -> Result<BenchmarkProgress, String> {
+    let played_names: HashSet<String> =
+        collect_from_folders(&find_stats_paths()?, |stats_path| collect_runs(stats_path, &scenarios, &cache))
+            .0
+            .into_iter()
+            .map(|stat| stat.scenario_name)
+            .collect();
+
+    let missing: Vec<String> = scenarios
+        .iter()
+        .filter(|scenario| !played_names.contains(scenario.as_str()))
+        .cloned()
+        .collect();
+
+    Ok(BenchmarkProgress {
+        played: scenarios.len() - missing.len(),
+        total: scenarios.len(),
+        missing
+    })
+}
+
+// Numeric Steam account IDs found under the userdata folder next to each
+// library's steamapps. Missing or unreadable folders are skipped.
+#[tauri::command]
+fn list_steam_users() -> Vec<String> {
+    let Ok(libraries) = steam_library_candidates() else {
+        return Vec::new();
+    };
+
+    let mut users: Vec<String> = libraries
+        .iter()
+        .filter_map(|library_path| fs::read_dir(library_path.parent()?.join("userdata")).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    users.sort();
+    users.dedup();
+
+    users
+}
+
+// JSON file in the app config directory holding the tracked scenario list
+fn tracked_scenarios_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to find the app config directory: {}", e))?;
+    Ok(config_dir.join("tracked_scenarios.json"))
+}
+
+#[tauri::command]
+fn save_tracked_scenarios(app: AppHandle, scenarios: Vec<String>) -> Result<(), String> {
+    let path = tracked_scenarios_path(&app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let json = serde_json::to_string_pretty(&scenarios)
+        .map_err(|e| format!("Failed to serialize scenarios: {}", e))?;
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// The list saved by save_tracked_scenarios, empty if nothing was saved yet
+#[tauri::command]
+fn load_tracked_scenarios(app: AppHandle) -> Result<Vec<String>, String> {
+    let path = tracked_scenarios_path(&app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let json = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid tracked scenarios in {}: {}", path.display(), e))
+}
+
+fn snapshots_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to find the app data directory: {}", e))?;
+    Ok(data_dir.join("snapshots"))
+}
+
+// Best score per scenario right now, None for scenarios without a run
+fn current_bests(scenarios: &[String], cache: &StatsCache) -> Result<HashMap<String, Option<f64>>, String> {
+    let mut bests: HashMap<String, Option<f64>> =
+        scenarios.iter().map(|scenario| (scenario.clone(), None)).collect();
+    for stat in discovered_highscores(scenarios, cache)?.stats {
+        bests.insert(stat.scenario_name, Some(stat.score));
+    }
+    Ok(bests)
+}
+
+// Saves the current best of each scenario to a timestamped JSON file in the
+// app data dir and returns the file name to pass to compare_to_snapshot.
+#[tauri::command]
+fn snapshot_benchmark(
+    app: AppHandle,
+    cache: tauri::State<StatsCache>,
+    scenarios: Vec<String>
+) -> Result<String, String> {
+    let created = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| format!("System clock is before 1970: {}", e))?
+        .as_secs() as i64;
+    let snapshot = BenchmarkSnapshot {
+        created,
+        bests: current_bests(&scenarios, &cache)?
+    };
+
+    let dir = snapshots_dir(&app)?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let file_name = format!("snapshot-{}.json", created);
+    let path = dir.join(&file_name);
+    let json = serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(file_name)
+}
+
+// Diffs the current bests against a file saved by snapshot_benchmark. Only the
+// scenarios recorded in the snapshot are compared.
+#[tauri::command]
+fn compare_to_snapshot(
+    app: AppHandle,
+    cache: tauri::State<StatsCache>,
+    snapshot_file: String
+) -> Result<SnapshotDiff, String> {
+    // Only bare file names, snapshots are never read from outside the snapshots dir
+    if Path::new(&snapshot_file).file_name().and_then(|name| name.to_str()) != Some(snapshot_file.as_str()) {
+        return Err(format!("Invalid snapshot file name: {}", snapshot_file));
+    }
+    let path = snapshots_dir(&app)?.join(&snapshot_file);
+    let json = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let snapshot: BenchmarkSnapshot = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid snapshot in {}: {}", path.display(), e))?;
+
+    let scenarios: Vec<String> = snapshot.bests.keys().cloned().collect();
+    let current = current_bests(&scenarios, &cache)?;
 
-    let filename = path.file_name()?.to_str()?;
-    let parts: Vec<&str> = filename.split(" - ").collect();
-    let scenario_name = parts.first()?.to_string();
-    let date_part = parts.get(2)?;
-    let date = date_part.replace(" Stats.csv", "");
+    let mut deltas = Vec::new();
+    let mut newly_played = Vec::new();
+    for (scenario, previous) in snapshot.bests {
+        let Some(current) = current.get(&scenario).copied().flatten() else {
+            continue;
+        };
+        match previous {
+            Some(previous) => deltas.push(ScenarioDelta {
+                scenario_name: scenario,
+                previous,
+                current,
+                delta: current - previous
+            }),
+            None => newly_played.push(scenario),
+        }
+    }
+    deltas.sort_by(|a, b| a.scenario_name.cmp(&b.scenario_name));
+    newly_played.sort();
 
-    let mut score = 0.0;
-    let mut kills = 0;
-    let mut hits = 0;
-    let mut misses = 0;
-    let mut fov_scale = String::new();
-    let mut fov = 0.0;
-    let mut resolution = String::new();
-    let mut avg_fps = 0.0;
-    let mut sens_scale = String::new();
-    let mut horiz_sens = 0.0;
-    let mut vert_sens = 0.0;
+    Ok(SnapshotDiff {
+        created: snapshot.created,
+        deltas,
+        newly_played
+    })
+}
+
+// Every "Key:,Value" pair in the footer of one stats file, trimmed and without
+// the comma prefix. Rows of the kill and weapon tables have commas before
+// their first colon and are skipped. Repeated keys keep their last value, the
+// same rule parse_csv_file follows.
+#[tauri::command]
+fn raw_footer(source_path: String) -> Result<HashMap<String, String>, String> {
+    let path = PathBuf::from(&source_path);
+    if !path.is_file() {
+        return Err(format!("Stats file not found: {}", source_path));
+    }
 
+    let lines = stats_file_lines(&path).map_err(|reason| format!("{}: {}", source_path, reason))?;
+
+    let mut footer = HashMap::new();
     for line in lines {
-        let parts: Vec<&str> = line.split(':').collect();
-        if parts.len() != 2 { continue; }
+        let line = line.map_err(|reason| format!("{}: {}", source_path, reason))?;
+        let Some((key, value)) = line.split_once(':') else { continue; };
+        if key.contains(',') || key.trim().is_empty() {
+            continue;
+        }
+        footer.insert(key.trim().to_string(), value.trim().trim_start_matches(',').to_string());
+    }
 
-        let key = parts[0].trim();
-        let value = parts[1].trim();
+    Ok(footer)
+}
 
-        match key {
-            "Score" => score = value.trim_start_matches(',').parse().unwrap_or(0.0),
-            "Kills" => kills = value.trim_start_matches(',').parse().unwrap_or(0),
-            "Hit Count" => hits = value.trim_start_matches(',').parse().unwrap_or(0),
-            "Miss Count" => misses = value.trim_start_matches(',').parse().unwrap_or(0),
-            "FOVScale" => fov_scale = value.trim_start_matches(',').to_string(),
-            "FOV" => fov = value.trim_start_matches(',').parse().unwrap_or(0.0),
-            "Resolution" => resolution = value.trim_start_matches(',').to_string(),
-            "Avg FPS" => avg_fps = value.trim_start_matches(',').parse().unwrap_or(0.0),
-            "Sens Scale" => sens_scale = value.trim_start_matches(',').to_string(),
-            "Horiz Sens" => horiz_sens = value.trim_start_matches(',').parse().unwrap_or(0.0),
-            "Vert Sens" => vert_sens = value.trim_start_matches(',').parse().unwrap_or(0.0),
-            _ => {}
+// Hex SHA-256 of the file's raw bytes followed by a newline and
+// "scenario|date|score|kills|hits|misses" from the parsed run, score with two
+// decimals. Anyone with the file can recompute it, so an edited file or an
+// edited summary no longer matches a fingerprint shared earlier.
+#[tauri::command]
+fn run_fingerprint(source_path: String) -> Result<String, String> {
+    let path = PathBuf::from(&source_path);
+    if !path.is_file() {
+        return Err(format!("Stats file not found: {}", source_path));
+    }
+
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", source_path, e))?;
+    let stat = parse_csv_file(&path).map_err(|reason| format!("{}: {}", source_path, reason))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    hasher.update(
+        format!(
+            "\n{}|{}|{:.2}|{}|{}|{}",
+            stat.scenario_name, stat.date, stat.score, stat.kills, stat.hits, stat.misses
+        )
+        .as_bytes(),
+    );
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Best run of a single scenario, picked the same way as get_stats but without
+// going through the scenario list machinery.
+#[tauri::command]
+fn get_best(cache: tauri::State<StatsCache>, scenario: String) -> Result<Option<StatsResult>, String> {
+    let stats_paths = find_stats_paths()?;
+
+    Ok(scenario_runs(&stats_paths, &scenario, &cache)
+        .into_iter()
+        .reduce(|best, stat| if is_better_run(&stat, &best, Tiebreak::default()) { stat } else { best }))
+}
+
+fn number_diff(a: Option<f64>, b: Option<f64>) -> NumberDiff {
+    NumberDiff { a, b, delta: a.zip(b).map(|(a, b)| b - a) }
+}
+
+fn text_diff(a: &str, b: &str) -> TextDiff {
+    TextDiff { a: a.to_string(), b: b.to_string(), changed: a != b }
+}
+
+// Field by field differences between two runs given by their source paths,
+// e.g. a best and the latest attempt. Deltas are b - a.
+#[tauri::command]
+fn diff_runs(cache: tauri::State<StatsCache>, path_a: String, path_b: String) -> Result<RunDiff, String> {
+    let mut runs = Vec::new();
+    for source_path in [&path_a, &path_b] {
+        let path = PathBuf::from(source_path);
+        if !path.is_file() {
+            return Err(format!("Stats file not found: {}", source_path));
         }
+        runs.push(cache.parse(&path, true).map_err(|reason| format!("{}: {}", source_path, reason))?);
     }
+    let (a, b) = (&runs[0], &runs[1]);
 
-    let sens_cm = if sens_scale == "cm/360" {
-        Some((horiz_sens, vert_sens))
-    } else {
-        None
+    Ok(RunDiff {
+        score: number_diff(Some(a.score), Some(b.score)),
+        accuracy: number_diff(a.accuracy, b.accuracy),
+        avg_fps: number_diff(a.avg_fps, b.avg_fps),
+        fov: number_diff(Some(a.fov), Some(b.fov)),
+        fov_scale: text_diff(&a.fov_scale, &b.fov_scale),
+        horiz_sens: number_diff(Some(a.horiz_sens), Some(b.horiz_sens)),
+        vert_sens: number_diff(Some(a.vert_sens), Some(b.vert_sens)),
+        sens_scale: text_diff(&a.sens_scale, &b.sens_scale)
+    })
+}
+
+// Canonical form of a path that may not exist yet: the deepest existing
+// ancestor is canonicalized and the missing components are appended to it.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    let mut resolved = loop {
+        if let Ok(canonical) = fs::canonicalize(existing) {
+            break canonical;
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(component)) => {
+                missing.push(component);
+                existing = parent;
+            }
+            _ => break path.to_path_buf(),
+        }
     };
 
-    Some(StatsResult {
-        scenario_name,
-        score,
-        kills,
-        hits,
-        misses,
-        fov_scale,
-        fov,
-        resolution,
-        avg_fps,
-        sens_cm,
-        date
+    // Nothing below `existing` is on disk, so ".." can be resolved lexically
+    for component in missing.into_iter().rev() {
+        match component {
+            std::path::Component::ParentDir => {
+                resolved.pop();
+            }
+            std::path::Component::CurDir => {}
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+// fs::rename can't move a file to another volume, so there the file is
+// copied, the copy compared with the original, and only then the original
+// removed. A copy that fails or doesn't match is removed again.
+fn move_file(source: &Path, dest: &Path) -> std::io::Result<()> {
+    match fs::rename(source, dest) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+        result => return result,
+    }
+
+    let copied = fs::copy(source, dest).and_then(|_| {
+        if fs::read(source)? == fs::read(dest)? {
+            Ok(())
+        } else {
+            Err(std::io::Error::other("the copy doesn't match the original"))
+        }
+    });
+    if let Err(e) = copied {
+        let _ = fs::remove_file(dest);
+        return Err(e);
+    }
+    fs::remove_file(source)
+}
+
+// Moves every run of `scenario` except the best `keep_best` into `dest_dir` and
+// returns the paths that were moved. Files are only ever moved, never deleted
+// or overwritten. The stats folder itself or any folder inside it is refused
+// as the archive, before anything is created. If a move fails the result still
+// lists the files moved before it, with the failure in `error`.
+#[tauri::command]
+fn archive_old_stats(
+    cache: tauri::State<StatsCache>,
+    scenario: String,
+    keep_best: usize,
+    dest_dir: String
+) -> Result<ArchiveResult, String> {
+    let stats_paths = find_stats_paths()?;
+    if stats_paths.is_empty() {
+        return Err("No stats path found".into());
+    }
+    let dest_dir = PathBuf::from(dest_dir);
+
+    let dest = resolve_path(&dest_dir);
+    if stats_paths.iter().any(|stats_path| dest.starts_with(resolve_path(stats_path))) {
+        return Err("The archive folder must be outside the stats folder".into());
+    }
+    fs::create_dir_all(&dest_dir)
+        .map_err(|e| format!("Failed to create {}: {}", dest_dir.display(), e))?;
+
+    let mut runs = scenario_runs(&stats_paths, &scenario, &cache);
+    runs.sort_by(best_first);
+
+    let moves: Vec<(PathBuf, PathBuf)> = runs
+        .iter()
+        .skip(keep_best)
+        .filter_map(|stat| {
+            let source = PathBuf::from(&stat.source_path);
+            let dest = dest_dir.join(source.file_name()?);
+            Some((source, dest))
+        })
+        .collect();
+
+    // Check every destination up front so a name clash doesn't leave a half-done archive
+    if let Some((_, dest)) = moves.iter().find(|(_, dest)| dest.exists()) {
+        return Err(format!("{} already exists, nothing was moved", dest.display()));
+    }
+
+    let mut moved = Vec::new();
+    for (source, dest) in moves {
+        if let Err(e) = move_file(&source, &dest) {
+            return Ok(ArchiveResult {
+                moved,
+                error: Some(format!("Failed to move {}: {}", source.display(), e))
+            });
+        }
+        moved.push(source.to_string_lossy().into_owned());
+    }
+
+    Ok(ArchiveResult { moved, error: None })
+}
+
+// Whether KovaaK's is currently running, for refreshing stats once it exits.
+// The game is FPSAimTrainer.exe / FPSAimTrainer-Win64-Shipping.exe on Windows
+// and runs the same executables through Proton on Linux, where process names
+// are cut to 15 characters, so matching is on a case-insensitive prefix.
+#[tauri::command]
+fn is_kovaak_running() -> bool {
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::All, true);
+
+    system.processes().values().any(|process| {
+        process
+            .name()
+            .to_str()
+            .is_some_and(|name| name.to_ascii_lowercase().starts_with("fpsaimtrainer"))
     })
 }
 
-fn get_steam_library_paths(install_path: &str) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
-    let library_file = PathBuf::from(install_path)
-        .join("steamapps")
-        .join("libraryfolders.vdf");
+// How far the best run of a scenario is ahead of the second best run, for
+// showing "+120 over previous best". None with fewer than two runs.
+#[tauri::command]
+fn improvement_over_previous(
+    cache: tauri::State<StatsCache>,
+    scenario: String
+) -> Result<Option<f64>, String> {
+    let stats_paths = find_stats_paths()?;
 
-    if let Ok(content) = fs::read_to_string(&library_file) {
-        for line in content.lines() {
-            if let Some(path) = line.split('"').nth(3) {
-                let library_path = PathBuf::from(path).join("steamapps");
-                paths.push(library_path);
+    let mut scores: Vec<f64> = scenario_runs(&stats_paths, &scenario, &cache)
+        .iter()
+        .map(|stat| stat.score)
+        .collect();
+    scores.sort_by(|a, b| b.total_cmp(a));
+
+    Ok(match scores.as_slice() {
+        [best, previous, ..] => Some(best - previous),
+        _ => None,
+    })
+}
+
+// Groups a scenario's runs into play sessions. A gap of more than
+// `gap_minutes` between consecutive runs starts a new session. Runs whose
+// filename date didn't parse can't be placed and are left out.
+#[tauri::command]
+fn get_sessions(
+    cache: tauri::State<StatsCache>,
+    scenario: String,
+    gap_minutes: u32
+) -> Result<Vec<Session>, String> {
+    let stats_paths = find_stats_paths()?;
+
+    let mut runs: Vec<(i64, f64)> = scenario_runs(&stats_paths, &scenario, &cache)
+        .into_iter()
+        .filter_map(|stat| stat.timestamp.map(|timestamp| (timestamp, stat.score)))
+        .collect();
+    runs.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let gap = i64::from(gap_minutes) * 60;
+    let mut sessions: Vec<Session> = Vec::new();
+
+    for (timestamp, score) in runs {
+        match sessions.last_mut() {
+            Some(session) if timestamp - session.end <= gap => {
+                session.end = timestamp;
+                session.run_count += 1;
+                session.best_score = session.best_score.max(score);
             }
+            _ => sessions.push(Session {
+                start: timestamp,
+                end: timestamp,
+                run_count: 1,
+                best_score: score
+            })
         }
     }
 
-    // Add the default library path as well
-    paths.push(PathBuf::from(install_path).join("steamapps"));
+    Ok(sessions)
+}
 
-    paths
+// Writes the get_stats result for `scenarios` to `out_path` as pretty JSON and
+// returns how many scenarios were written.
+#[tauri::command]
+fn export_stats(
+    cache: tauri::State<StatsCache>,
+    scenarios: Vec<String>,
+    out_path: String
+) -> Result<usize, String> {
+    let result = discovered_highscores(&scenarios, &cache)?;
+    let json = serde_json::to_string_pretty(&result)
+        .map_err(|e| format!("Failed to serialize stats: {}", e))?;
+
+    let out_path = PathBuf::from(out_path);
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&out_path, json)
+        .map_err(|e| format!("Failed to write {}: {}", out_path.display(), e))?;
+
+    Ok(result.stats.len())
 }
 
+// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
 
+// Writes one row per scenario's highscore to `out_path` as CSV and returns the
+// number of rows. Rank is left empty for scenarios not in the threshold table.
 #[tauri::command]
-fn get_stats(scenarios: Vec<String>) -> Result<PathResult, String> {
-    #[cfg(target_os = "windows")]
-    {
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        let steam_key = hklm
-            .open_subkey(r"SOFTWARE\WOW6432Node\Valve\Steam")
-            .or_else(|_| hklm.open_subkey(r"SOFTWARE\Valve\Steam"))
-            .map_err(|e| format!("Failed to find Steam registry key: {}", e))?;
-
-        let install_path: String = steam_key
-            .get_value("InstallPath")
-            .map_err(|e| format!("Failed to get Steam install path: {}", e))?;
-
-        let library_paths = get_steam_library_paths(&install_path);
-
-        // Temp fallback path for testing
-        let fallback_path = PathBuf::from(r"S:\SteamLibrary\steamapps\common\FPSAimTrainer\FPSAimTrainer\stats");
-        let mut all_paths = library_paths.clone();
-        all_paths.push(fallback_path);
-
-        for library_path in library_paths {
-            let stats_path = library_path.join("common/FPSAimTrainer/FPSAimTrainer/stats");
-
-            if stats_path.exists() {
-                let mut stats = Vec::new();
-                let mut scenario_highscores: HashMap<String, StatsResult> = HashMap::new();
-
-                if let Ok(entries) = fs::read_dir(&stats_path) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.extension().and_then(|s| s.to_str()) == Some("csv") {
-                            if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                                if scenarios.iter().any(|scenario| filename.starts_with(scenario)) {
-                                    if let Some(stat) = parse_csv_file(&path) {
-                                        let entry = scenario_highscores.entry(stat.scenario_name.clone()).or_insert(stat.clone());
-                                        if stat.score > entry.score {
-                                            *entry = stat;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+fn export_summary_csv(
+    cache: tauri::State<StatsCache>,
+    thresholds: tauri::State<ThresholdTable>,
+    scenarios: Vec<String>,
+    out_path: String
+) -> Result<usize, String> {
+    let mut stats = discovered_highscores(&scenarios, &cache)?.stats;
+    stats.sort_by(|a, b| a.scenario_name.cmp(&b.scenario_name));
 
-                for (_, stat) in scenario_highscores {
-                    stats.push(stat);
-                }
+    let table = thresholds.0.lock().unwrap();
+    let mut csv = String::from("scenario_name,best_score,accuracy,avg_fps,date,rank\n");
+    for stat in &stats {
+        let accuracy = stat.accuracy.map(|accuracy| accuracy.to_string()).unwrap_or_default();
+        let avg_fps = stat.avg_fps.map(|fps| fps.to_string()).unwrap_or_default();
+        let rank = table
+            .get(&stat.scenario_name)
+            .map(|thresholds| rank_info(thresholds, stat.score).rank)
+            .unwrap_or_default();
 
-                return Ok(PathResult {
-                    stats_path: stats_path.to_string_lossy().into_owned(),
-                    exists: true,
-                    stats
-                });
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&stat.scenario_name),
+            stat.score,
+            accuracy,
+            avg_fps,
+            csv_field(&stat.date),
+            csv_field(&rank)
+        ));
+    }
+
+    let out_path = PathBuf::from(out_path);
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&out_path, csv)
+        .map_err(|e| format!("Failed to write {}: {}", out_path.display(), e))?;
+
+    Ok(stats.len())
+}
+
+// Checks a run against benchmark rules. An empty result means the run is valid.
+#[tauri::command]
+fn validate_run(stat: StatsResult, rules: BenchmarkRules) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |code: &str, message: String| {
+        violations.push(RuleViolation { code: code.to_string(), message });
+    };
+
+    if let Some(min_fps) = rules.min_fps {
+        match stat.avg_fps {
+            Some(avg_fps) if avg_fps < min_fps => {
+                violation("avg_fps_below_min", format!("avg_fps {} below {}", avg_fps, min_fps));
             }
+            Some(_) => {}
+            None => violation("avg_fps_unknown", "avg_fps was not recorded".to_string()),
         }
+    }
 
-        Ok(PathResult {
-            stats_path: "No stats path found".into(),
-            exists: false,
-            stats: Vec::new()
-        })
+    if let Some(allowed) = &rules.allowed_fov_scales {
+        if !allowed.iter().any(|scale| scale.eq_ignore_ascii_case(&stat.fov_scale)) {
+            violation(
+                "fov_scale_not_allowed",
+                format!("FOV scale \"{}\" is not one of {}", stat.fov_scale, allowed.join(", ")),
+            );
+        }
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        let home = std::env::var("HOME").map_err(|_| "Could not find HOME directory")?;
-        let steam_paths = vec![
-            format!("{}/.local/share/Steam", home),
-            format!("{}/.steam/steam", home),
-        ];
+    if rules.min_width.is_some() || rules.min_height.is_some() {
+        let min_width = rules.min_width.unwrap_or(0);
+        let min_height = rules.min_height.unwrap_or(0);
+        match parse_resolution(&stat.resolution) {
+            Some((width, height)) if width < min_width || height < min_height => violation(
+                "resolution_below_min",
+                format!("resolution {}x{} below {}x{}", width, height, min_width, min_height),
+            ),
+            Some(_) => {}
+            None => violation(
+                "resolution_unknown",
+                format!("resolution \"{}\" could not be read", stat.resolution),
+            ),
+        }
+    }
 
-        for base_path in steam_paths {
-            let mut stats_path = PathBuf::from(&base_path);
-            stats_path.push("steamapps/common/FPSAimTrainer/FPSAimTrainer/stats");
-
-            if stats_path.exists() {
-                let mut stats = Vec::new();
-                let mut scenario_highscores: HashMap<String, StatsResult> = HashMap::new();
-
-                if let Ok(entries) = fs::read_dir(&stats_path) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.extension().and_then(|s| s.to_str()) == Some("csv") {
-                            if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                                if scenarios.iter().any(|scenario| filename.starts_with(scenario)) {
-                                    if let Some(stat) = parse_csv_file(&path) {
-                                        let entry = scenario_highscores.entry(stat.scenario_name.clone()).or_insert(stat.clone());
-                                        if stat.score > entry.score {
-                                            *entry = stat;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    if let Some(sens_ratio) = stat.sens_ratio {
+        if (sens_ratio - 1.0).abs() > SENS_RATIO_EPSILON {
+            violation(
+                "sens_ratio_mismatch",
+                format!("horizontal/vertical sens ratio {:.3} is not 1:1", sens_ratio),
+            );
+        }
+    }
 
-                for (_, stat) in scenario_highscores {
-                    stats.push(stat);
-                }
+    violations
+}
 
-                return Ok(PathResult {
-                    stats_path: stats_path.to_string_lossy().into_owned(),
-                    exists: true,
-                    stats
-                });
-            }
+// Compares the best score per scenario between two arbitrary stats folders.
+#[tauri::command]
+fn compare_folders(
+    cache: tauri::State<StatsCache>,
+    path_a: String,
+    path_b: String,
+    scenarios: Vec<String>
+) -> Result<Vec<ScenarioComparison>, String> {
+    let mut highscores = Vec::new();
+    for path in [&path_a, &path_b] {
+        let stats_path = PathBuf::from(path);
+        if !stats_path.is_dir() {
+            return Err(format!("Stats folder not found: {}", path));
         }
+        let (runs, _, _) = collect_runs(&stats_path, &scenarios, &cache);
+        highscores.push(select_highscores(runs, &HashMap::new(), Tiebreak::default(), &HashSet::new()));
+    }
+    let (best_a, best_b) = (&highscores[0], &highscores[1]);
 
-        Ok(PathResult {
-            stats_path: format!("{}/.steam/steam/steamapps/common/FPSAimTrainer/FPSAimTrainer/stats", home),
-            exists: false,
-            stats: Vec::new()
+    let mut names: Vec<&String> = best_a.keys().chain(best_b.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let score_a = best_a.get(name).map(|stat| stat.score);
+            let score_b = best_b.get(name).map(|stat| stat.score);
+            ScenarioComparison {
+                scenario_name: name.clone(),
+                best_a: score_a,
+                best_b: score_b,
+                delta: score_a.zip(score_b).map(|(a, b)| b - a)
+            }
         })
+        .collect())
+}
+
+// Opens the discovered stats folder in the OS file manager and returns its path.
+#[tauri::command]
+fn reveal_stats_folder(app: AppHandle) -> Result<String, String> {
+    let stats_path = find_stats_path(&[])?.ok_or("No stats path found")?;
+    let stats_path = stats_path.to_string_lossy().into_owned();
+
+    app.opener()
+        .open_path(stats_path.clone(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", stats_path, e))?;
+
+    Ok(stats_path)
+}
+
+// Opens one run's stats file with the OS default app. Only files inside the
+// discovered stats folder (after resolving symlinks and "..") are opened.
+#[tauri::command]
+fn open_run_file(app: AppHandle, source_path: String) -> Result<(), String> {
+    let stats_paths = find_stats_paths()?;
+    if stats_paths.is_empty() {
+        return Err("No stats path found".into());
+    }
+    let file = fs::canonicalize(&source_path)
+        .map_err(|e| format!("Stats file not found: {} ({})", source_path, e))?;
+
+    let in_stats_dir = stats_paths
+        .iter()
+        .filter_map(|stats_path| fs::canonicalize(stats_path).ok())
+        .any(|stats_dir| file.starts_with(stats_dir));
+    if !in_stats_dir || !file.is_file() || !is_stats_file(&file) {
+        return Err(format!("Not a stats file in {}: {}", folders_label(&stats_paths), source_path));
     }
+
+    let file = canonical_path_string(&file);
+    app.opener()
+        .open_path(file.clone(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", file, e))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -242,7 +3384,53 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_window_state::Builder::new().build()) 
         .plugin(tauri_plugin_opener::init()) 
-        .invoke_handler(tauri::generate_handler![get_stats])
+        .manage(StatsCache::default())
+        .manage(StatsWatcher::default())
+        .manage(ThresholdTable::default())
+        .invoke_handler(tauri::generate_handler![
+            get_stats,
+            get_stats_history,
+            list_scenarios,
+            clear_stats_cache,
+            start_watching,
+            stop_watching,
+            convert_sens,
+            rank_for_score,
+            scenario_summary,
+            get_stats_history_smoothed,
+            export_stats,
+            export_summary_csv,
+            validate_run,
+            compare_folders,
+            reveal_stats_folder,
+            get_sessions,
+            improvement_over_previous,
+            benchmark_progress,
+            overall_benchmark_score,
+            recent_runs,
+            list_steam_users,
+            save_tracked_scenarios,
+            load_tracked_scenarios,
+            pbs_since,
+            raw_footer,
+            get_best,
+            archive_old_stats,
+            locate_stats,
+            is_kovaak_running,
+            load_benchmark_table,
+            play_calendar,
+            snapshot_benchmark,
+            compare_to_snapshot,
+            open_run_file,
+            get_stats_from_zip,
+            rank_distribution,
+            flag_outdated_runs,
+            diff_runs,
+            daily_bests,
+            run_fingerprint,
+            top_runs,
+            set_benchmark_table
+        ])
         .setup(|app| {
             // Restore window state for the main window at startup
             if let Some(window) = app.get_webview_window("main") {
@@ -267,3 +3455,352 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(scenario: &str, date: &str, score: f64, hits: i32, misses: i32) -> StatsResult {
+        let filename = format!("{} - Challenge - {} Stats.csv", scenario, date);
+        let content = format!("Score:,{}\nHit Count:,{}\nMiss Count:,{}\n", score, hits, misses);
+        parse_csv_content(&content, &filename).unwrap()
+    }
+
+    fn best(runs: Vec<StatsResult>, tiebreak: Tiebreak) -> HashMap<String, StatsResult> {
+        select_highscores(runs, &HashMap::new(), tiebreak, &HashSet::new())
+    }
+
+    #[test]
+    fn single_run_is_the_best() {
+        let highscores = best(vec![run("VT Pasu", "2024.01.01-10.00.00", 800.0, 10, 5)], Tiebreak::default());
+
+        assert_eq!(highscores.len(), 1);
+        assert_eq!(highscores["VT Pasu"].score, 800.0);
+    }
+
+    #[test]
+    fn highest_score_wins_in_any_order() {
+        let runs = vec![
+            run("VT Pasu", "2024.01.01-10.00.00", 800.0, 10, 5),
+            run("VT Pasu", "2024.01.02-10.00.00", 950.5, 10, 5),
+            run("VT Pasu", "2024.01.03-10.00.00", 900.0, 10, 5),
+        ];
+        let mut reversed = runs.clone();
+        reversed.reverse();
+
+        assert_eq!(best(runs, Tiebreak::default())["VT Pasu"].score, 950.5);
+        assert_eq!(best(reversed, Tiebreak::default())["VT Pasu"].score, 950.5);
+    }
+
+    #[test]
+    fn exact_tie_keeps_the_latest_run() {
+        let runs = vec![
+            run("VT Pasu", "2024.01.02-10.00.00", 900.0, 10, 5),
+            run("VT Pasu", "2024.01.01-10.00.00", 900.0, 10, 5),
+        ];
+        let mut reversed = runs.clone();
+        reversed.reverse();
+
+        assert_eq!(best(runs, Tiebreak::default())["VT Pasu"].date, "2024.01.02-10.00.00");
+        assert_eq!(best(reversed, Tiebreak::default())["VT Pasu"].date, "2024.01.02-10.00.00");
+    }
+
+    #[test]
+    fn exact_tie_by_accuracy() {
+        let runs = vec![
+            run("VT Pasu", "2024.01.02-10.00.00", 900.0, 10, 10),
+            run("VT Pasu", "2024.01.01-10.00.00", 900.0, 18, 2),
+        ];
+
+        assert_eq!(best(runs, Tiebreak::Accuracy)["VT Pasu"].date, "2024.01.01-10.00.00");
+    }
+
+    #[test]
+    fn scenarios_do_not_interfere() {
+        let runs = vec![
+            run("VT Pasu", "2024.01.01-10.00.00", 900.0, 10, 5),
+            run("VT Popcorn", "2024.01.02-10.00.00", 3000.0, 10, 5),
+            run("VT Pasu", "2024.01.03-10.00.00", 700.0, 10, 5),
+            run("VT Popcorn", "2024.01.04-10.00.00", 2500.0, 10, 5),
+        ];
+        let highscores = best(runs, Tiebreak::default());
+
+        assert_eq!(highscores.len(), 2);
+        assert_eq!(highscores["VT Pasu"].score, 900.0);
+        assert_eq!(highscores["VT Popcorn"].score, 3000.0);
+    }
+
+    #[test]
+    fn aliases_share_one_highscore() {
+        let runs = vec![
+            run("VT Pasu", "2024.01.01-10.00.00", 900.0, 10, 5),
+            run("VT Pasu Old", "2024.01.02-10.00.00", 950.0, 10, 5),
+        ];
+        let aliases = HashMap::from([("VT Pasu Old".to_string(), "VT Pasu".to_string())]);
+        let highscores = select_highscores(runs, &aliases, Tiebreak::default(), &HashSet::new());
+
+        assert_eq!(highscores.len(), 1);
+        assert_eq!(highscores["VT Pasu"].scenario_name, "VT Pasu Old");
+    }
+
+    #[test]
+    fn aliased_runs_report_the_canonical_name() {
+        let runs = vec![
+            run("VT Pasu", "2024.01.01-10.00.00", 900.0, 10, 5),
+            run("VT Pasu Old", "2024.01.02-10.00.00", 950.0, 10, 5),
+        ];
+        let options = HighscoreOptions {
+            aliases: HashMap::from([("VT Pasu Old".to_string(), "VT Pasu".to_string())]),
+            ..Default::default()
+        };
+        let (stats, _) = select_from_runs(runs, &["VT Pasu".to_string()], &options);
+
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].scenario_name, "VT Pasu");
+        assert_eq!(stats[0].score, 950.0);
+    }
+
+    #[test]
+    fn unaliased_runs_keep_their_written_name() {
+        let mut stat = run("VT Smoothbot ", "2024.01.01-10.00.00", 900.0, 10, 5);
+        apply_canonical_name(&mut stat, &HashMap::from([("VT Pasu Old".to_string(), "VT Pasu".to_string())]));
+
+        assert_eq!(stat.scenario_name, "VT Smoothbot ");
+    }
+
+    #[test]
+    fn lower_is_better_keeps_the_lowest_score() {
+        let runs = vec![
+            run("Time Trial", "2024.01.01-10.00.00", 42.5, 10, 5),
+            run("Time Trial", "2024.01.02-10.00.00", 40.0, 10, 5),
+        ];
+        let lower_is_better = HashSet::from(["Time Trial".to_string()]);
+        let highscores = select_highscores(runs, &HashMap::new(), Tiebreak::default(), &lower_is_better);
+
+        assert_eq!(highscores["Time Trial"].score, 40.0);
+    }
+
+    #[test]
+    fn bom_and_crlf_content_parses() {
+        let content = "\u{feff}Score:,12345\r\nHit Count:,10\r\nMiss Count:,2\r\n";
+        let stat = parse_csv_content(content, "VT Pasu - Challenge - 2024.01.01-10.00.00 Stats.csv").unwrap();
+
+        assert_eq!(stat.score, 12345.0);
+        assert_eq!(stat.hits, 10);
+        assert_eq!(stat.misses, 2);
+    }
+
+    #[test]
+    fn streamed_lines_drop_bom_and_crlf() {
+        let path = std::env::temp_dir().join(format!("vt-bom-{}.csv", std::process::id()));
+        fs::write(&path, "\u{feff}Score:,12345\r\nHit Count:,10\r\n").unwrap();
+        let lines: Vec<String> = stats_file_lines(&path).unwrap().map(Result::unwrap).collect();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines, ["Score:,12345", "Hit Count:,10"]);
+    }
+
+    #[test]
+    fn bak_runs_count_as_stats_files() {
+        let filename = "VT Pasu - Challenge - 2024.01.02-10.00.00 Stats.csv.bak";
+        assert!(is_stats_file(Path::new(filename)));
+
+        let bak = parse_csv_content("Score:,950\nHit Count:,10\nMiss Count:,5\n", filename).unwrap();
+        assert_eq!(bak.scenario_name, "VT Pasu");
+        assert_eq!(bak.date, "2024.01.02-10.00.00");
+
+        let runs = vec![run("VT Pasu", "2024.01.01-10.00.00", 900.0, 10, 5), bak];
+        assert_eq!(best(runs, Tiebreak::default())["VT Pasu"].score, 950.0);
+    }
+
+    #[test]
+    fn library_folders_modern_layout() {
+        let content = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"label"		""
+		"apps"
+		{
+			"228980"		"250798478"
+			"824270"		"1973621776"
+		}
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+		"apps"
+		{
+			"1"		"0"
+		}
+	}
+}
+"#;
+
+        assert_eq!(parse_library_folders(content), [r"C:\Program Files (x86)\Steam", r"D:\SteamLibrary"]);
+    }
+
+    #[test]
+    fn library_folders_legacy_layout() {
+        let content = r#"
+"LibraryFolders"
+{
+	"TimeNextStatsReport"		"1700000000"
+	"ContentStatsID"		"-1234567890"
+	"1"		"D:\\Games"
+	"2"		"E:\\Steam Library"
+}
+"#;
+
+        assert_eq!(parse_library_folders(content), [r"D:\Games", r"E:\Steam Library"]);
+    }
+
+    #[test]
+    fn scenario_names_keep_their_dashes() {
+        assert_eq!(
+            split_stats_filename("VT - Pasu Goated - Challenge - 2024.01.01-10.00.00 Stats.csv"),
+            Ok(("VT - Pasu Goated", "2024.01.01-10.00.00"))
+        );
+        assert_eq!(
+            split_stats_filename("VT Pasu - 2024.01.01-10.00.00 Stats.csv"),
+            Ok(("VT Pasu", "2024.01.01-10.00.00"))
+        );
+        assert_eq!(split_stats_filename("VT Pasu Stats.csv"), Err("missing date segment"));
+        assert_eq!(split_stats_filename("VT Pasu - Challenge - 2024.01.01-10.00.00.csv"), Err("missing date segment"));
+    }
+
+    #[test]
+    fn stats_extensions_match_in_any_case() {
+        for extension in ["csv", "CSV", "Csv"] {
+            let filename = format!("VT Pasu - Challenge - 2024.01.01-10.00.00 Stats.{}", extension);
+            assert!(is_stats_file(Path::new(&filename)), "{}", filename);
+        }
+        assert_eq!(
+            split_stats_filename("VT Pasu - Challenge - 2024.01.01-10.00.00 Stats.CSV"),
+            Ok(("VT Pasu", "2024.01.01-10.00.00"))
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_keep_the_last_value() {
+        let content = "Score:,900\nAvg FPS:,144.0\nHit Count:,10\nAvg FPS:,239.9\n";
+        let stat = parse_csv_content(content, "VT Pasu - Challenge - 2024.01.01-10.00.00 Stats.csv").unwrap();
+
+        assert_eq!(stat.avg_fps, Some(239.9));
+    }
+
+    #[test]
+    fn malformed_numbers_fail_with_the_raw_value() {
+        let filename = "VT Pasu - Challenge - 2024.01.01-10.00.00 Stats.csv";
+
+        let error = parse_csv_content("Score:,900\nFOV:,wide\n", filename).unwrap_err();
+        assert_eq!(error, "unparseable FOV \"wide\"");
+
+        let stat = parse_csv_content("Score:,900\nShots:,1 234\nHoriz Sens:,0,45\nDPI:,\n", filename).unwrap();
+        assert_eq!(stat.shots, Some(1234));
+        assert_eq!(stat.horiz_sens, 0.45);
+        assert_eq!(stat.dpi, None);
+    }
+
+    #[test]
+    fn scores_equal_after_rounding_tie() {
+        let earlier = run("VT Pasu", "2024.01.01-10.00.00", 12345.004, 10, 5);
+        let later = run("VT Pasu", "2024.01.02-10.00.00", 12345.001, 10, 5);
+
+        assert_eq!(earlier.score, later.score);
+        assert!(is_better_run(&later, &earlier, Tiebreak::default()));
+        assert!(!is_better_run(&earlier, &later, Tiebreak::default()));
+        assert_eq!(best(vec![later, earlier], Tiebreak::default())["VT Pasu"].date, "2024.01.02-10.00.00");
+    }
+
+    #[test]
+    fn parallel_scan_picks_the_latest_tie() {
+        let dir = std::env::temp_dir().join(format!("vt-ties-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for day in 1..=9 {
+            for hour in 0..24 {
+                let filename = format!("VT Pasu - Challenge - 2024.01.0{}-{:02}.00.00 Stats.csv", day, hour);
+                fs::write(dir.join(filename), "Score:,900\nHit Count:,10\nMiss Count:,5\n").unwrap();
+            }
+        }
+
+        for _ in 0..5 {
+            let (mut runs, skipped, _) = collect_runs(&dir, &["VT Pasu".to_string()], &StatsCache::default());
+            assert_eq!((runs.len(), skipped.len()), (216, 0));
+            assert_eq!(best(runs.clone(), Tiebreak::default())["VT Pasu"].date, "2024.01.09-23.00.00");
+            runs.reverse();
+            assert_eq!(best(runs, Tiebreak::default())["VT Pasu"].date, "2024.01.09-23.00.00");
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn kovaak_dates_are_local_unix_seconds() {
+        let timestamp = parse_kovaak_date("2024.07.01-18.30.00").unwrap();
+        let local = Local.timestamp_opt(timestamp, 0).unwrap().naive_local();
+
+        assert_eq!(local.to_string(), "2024-07-01 18:30:00");
+        assert_eq!(parse_iso_bound("2024-07-01T18:30:00", false), Ok(timestamp));
+        assert_eq!(parse_kovaak_date("2024.13.01-18.30.00"), None);
+    }
+
+    #[test]
+    fn gist_table_loads_every_tier() {
+        let json = r#"{
+            "Novice": {"Clicking": {"Dynamic": {"VT Pasu Novice S5": {"Silver": 500, "Iron": 300, "Gold": 600, "Bronze": 400}}}},
+            "Intermediate": {"Tracking": {"Precise": {"VT PGT Intermediate S5": {"Platinum": 2000}}}},
+            "Advanced": {"Switching": {"Speed": {"VT DotTS Advanced S5": {"Grandmaster": 1230}}}}
+        }"#;
+        let table = parse_benchmark_table(json).unwrap();
+
+        assert_eq!(table.len(), 3);
+        let novice: Vec<&str> = table["VT Pasu Novice S5"].iter().map(|(rank, _)| rank.as_str()).collect();
+        assert_eq!(novice, ["Iron", "Bronze", "Silver", "Gold"]);
+        assert_eq!(rank_info(&table["VT Pasu Novice S5"], 450.0).rank, "Bronze");
+    }
+
+    #[test]
+    fn resolved_archive_paths_stay_comparable() {
+        let stats = std::env::temp_dir().join(format!("vt-stats-{}", std::process::id()));
+        fs::create_dir_all(&stats).unwrap();
+        let stats_resolved = resolve_path(&stats);
+
+        assert!(resolve_path(&stats.join("archive").join("old")).starts_with(&stats_resolved));
+        assert!(resolve_path(&stats.join("archive").join("..")).starts_with(&stats_resolved));
+        assert!(!resolve_path(&stats.join("..").join("archive")).starts_with(&stats_resolved));
+        assert!(!stats.join("archive").exists());
+        fs::remove_dir_all(&stats).unwrap();
+    }
+
+    #[test]
+    fn moved_files_keep_their_contents() {
+        let dir = std::env::temp_dir().join(format!("vt-move-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (source, dest) = (dir.join("run.csv"), dir.join("archived.csv"));
+        fs::write(&source, "Score:,900\n").unwrap();
+
+        move_file(&source, &dest).unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "Score:,900\n");
+        assert!(move_file(&source, &dir.join("again.csv")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn repeated_folders_are_read_once() {
+        let dir = std::env::temp_dir().join(format!("vt-folders-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let filename = "VT Pasu - Challenge - 2024.01.01-10.00.00 Stats.csv";
+        fs::write(dir.join(filename), "Score:,900\n").unwrap();
+        let stats_paths = [dir.clone(), dir.join(".")];
+
+        assert_eq!(stats_file_names(&stats_paths), [filename]);
+        let (runs, _, _) = collect_from_folders(&stats_paths, |_| {
+            (vec![run("VT Pasu", "2024.01.01-10.00.00", 900.0, 10, 5)], Vec::new(), ScanCounts::default())
+        });
+        assert_eq!(runs.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -20,6 +20,7 @@ import {
   Difficulty,
   ENERGY_INCREMENT,
   RANK_COLORS,
+  StatsError,
   StatsResult,
   Subcategory,
   SUBCATEGORY_COLORS,
@@ -101,6 +102,11 @@ export default function App() {
         const data = await response.json();
         setBenchmarkData(data);
 
+        // The backend rank commands use the same thresholds
+        await invoke("set_benchmark_table", { json: JSON.stringify(data) }).catch((error) =>
+          console.error("Error loading thresholds:", error)
+        );
+
         // Extract all unique scenario names from benchmark data
         const allScenarios = new Set<string>();
         Object.values(data).forEach((categories) => {
@@ -143,30 +149,34 @@ export default function App() {
   }, []);
 
   const fetchScores = async (scenarios: string[]) => {
+    // Create initial state with all scenarios
+    const initialScores = scenarios.reduce((acc, scenario) => {
+      acc[scenario] = {
+        rank: "Unranked",
+        progress: 0,
+        energy: 0,
+        highScore: 0,
+        kills: 0,
+        hits: 0,
+        misses: 0,
+        fov: 0,
+        fov_scale: "",
+        resolution: "",
+        avg_fps: null,
+        sens_cm: null,
+        date: "",
+      };
+      return acc;
+    }, {} as BenchmarkState);
+
     try {
-      const rustResponse: { stats: StatsResult[] } = await invoke("get_stats", {
+      const rustResponse: { stats: StatsResult[]; error: string | null } = await invoke("get_stats", {
         scenarios,
       });
-
-      // Create initial state with all scenarios
-      const initialScores = scenarios.reduce((acc, scenario) => {
-        acc[scenario] = {
-          rank: "Unranked",
-          progress: 0,
-          energy: 0,
-          highScore: 0,
-          kills: 0,
-          hits: 0,
-          misses: 0,
-          fov: 0,
-          fov_scale: "",
-          resolution: "",
-          avg_fps: 0,
-          sens_cm: null,
-          date: "",
-        };
-        return acc;
-      }, {} as BenchmarkState);
+      // The stats folder exists but couldn't be read
+      if (rustResponse.error) {
+        console.error("Error fetching scores:", rustResponse.error);
+      }
 
       // Update with actual scores
       rustResponse.stats.forEach((stat) => {
@@ -186,11 +196,15 @@ export default function App() {
           date: stat.date,
         };
       });
-
-      setScores(initialScores);
     } catch (error) {
-      console.error("Error fetching scores:", error);
+      const statsError = error as StatsError;
+      // A missing Steam install or stats folder still shows every scenario as unranked
+      if (statsError.kind !== "steam_not_found" && statsError.kind !== "stats_folder_not_found") {
+        console.error("Error fetching scores:", statsError.message);
+      }
     }
+
+    setScores(initialScores);
   };
 
   const refreshScores = async () => {
@@ -506,7 +520,7 @@ export default function App() {
                             </div>
                             <div>
                               <strong>Avg FPS:</strong>{" "}
-                              {scoreData?.avg_fps.toFixed(2) || "-"}
+                              {scoreData?.avg_fps?.toFixed(1) ?? "-"}
                             </div>
                             <Divider my="xs" />
                             <div>
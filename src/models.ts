@@ -32,25 +32,37 @@ export type ScoreData = {
   fov: number;
   fov_scale: string;
   resolution: string;
-  avg_fps: number;
+  avg_fps: number | null;
   sens_cm: [number, number] | null;
   date: string;
 };
 
 export interface StatsResult {
   scenario_name: string;
+  category: string | null;
   score: number;
   kills: number;
   hits: number;
   misses: number;
+  sens_scale: string;
+  horiz_sens: number;
+  vert_sens: number;
+  sens_ratio: number | null; // horiz / vert
   sens_cm: [number, number] | null;
+  sens_cm_computed: [number, number] | null; // cm/360 converted from sens_scale when DPI is known
   fov: number;
   fov_scale: string;
   resolution: string;
-  avg_fps: number;
+  avg_fps: number | null;
   date: string;
 }
 
+export interface StatsError {
+  kind: "steam_not_found" | "stats_folder_not_found" | "io" | "invalid_date";
+  message: string;
+  searched_paths?: string[]; // only for stats_folder_not_found
+}
+
 export interface BenchmarkState {
   [key: string]: ScoreData;
 }